@@ -0,0 +1,351 @@
+#![allow(dead_code)]
+
+// Ordered binary tree like binarytree::BinaryTree, but balanced: the heights of the two subtrees of every node
+// differ by at most one, so the height of the tree is O(log n) whatever the order of the insertions.
+// Every node stores the height of its subtree. When an insertion or a deletion leaves a node unbalanced (a difference
+// of two), it's fixed on the way back up with one rotation, or two when the taller grandchild is on the inner side.
+// Like in BinaryTree, equal values are inserted to the left, although rotations may move them to the right.
+
+use crate::orderedset::{ self, SearchNode };
+
+struct Node<T> {
+    value: T,
+    left: Option<Box<Node<T>>>,
+    right: Option<Box<Node<T>>>,
+    height: usize, // of the subtree, 1 for a leaf
+}
+
+fn height<T>(node: &Option<Box<Node<T>>>) -> usize {
+    node.as_ref().map_or(0, |n| n.height)
+}
+
+impl<T: PartialOrd> Node<T> {
+    fn new(value: T) -> Node<T> {
+        Node {
+            value,
+            left: None,
+            right: None,
+            height: 1,
+        }
+    }
+
+    fn update(&mut self) {
+        self.height = 1 + height(&self.left).max(height(&self.right));
+    }
+
+    fn balance_factor(&self) -> isize {
+        (height(&self.left) as isize) - (height(&self.right) as isize)
+    }
+
+    fn rotate_right(mut this: Box<Node<T>>) -> Box<Node<T>> {
+        //      this          left
+        //     /    \        /    \
+        //   left    c  ->  a     this
+        //  /    \               /    \
+        // a      b             b      c
+        let mut left: Box<Node<T>> = this.left.take().unwrap();
+        this.left = left.right.take();
+        this.update();
+        left.right = Some(this);
+        left.update();
+        left
+    }
+
+    fn rotate_left(mut this: Box<Node<T>>) -> Box<Node<T>> {
+        // the mirror of rotate_right
+        let mut right: Box<Node<T>> = this.right.take().unwrap();
+        this.right = right.left.take();
+        this.update();
+        right.left = Some(this);
+        right.update();
+        right
+    }
+
+    fn rebalance(mut this: Box<Node<T>>) -> Box<Node<T>> {
+        this.update();
+        let balance: isize = this.balance_factor();
+        if balance > 1 {
+            if this.left.as_ref().unwrap().balance_factor() < 0 {
+                // the taller grandchild is left.right, which a single rotation would leave unbalanced
+                this.left = Some(Self::rotate_left(this.left.take().unwrap()));
+            }
+            return Self::rotate_right(this);
+        }
+        if balance < -1 {
+            if this.right.as_ref().unwrap().balance_factor() > 0 {
+                this.right = Some(Self::rotate_right(this.right.take().unwrap()));
+            }
+            return Self::rotate_left(this);
+        }
+        this
+    }
+
+    fn insert(this: Option<Box<Node<T>>>, value: T) -> Box<Node<T>> {
+        match this {
+            None => Box::new(Node::new(value)),
+            Some(mut this) => {
+                if value <= this.value {
+                    this.left = Some(Self::insert(this.left.take(), value));
+                } else {
+                    this.right = Some(Self::insert(this.right.take(), value));
+                }
+                Self::rebalance(this)
+            }
+        }
+    }
+
+    fn remove_min(mut this: Box<Node<T>>) -> (Option<Box<Node<T>>>, Box<Node<T>>) {
+        // returns the subtree without its smallest node, and that node
+        match this.left.take() {
+            None => (this.right.take(), this),
+            Some(left) => {
+                let (left, min) = Self::remove_min(left);
+                this.left = left;
+                (Some(Self::rebalance(this)), min)
+            }
+        }
+    }
+
+    fn delete(mut this: Box<Node<T>>, target: &T) -> Option<Box<Node<T>>> {
+        if target < &this.value {
+            if let Some(left) = this.left.take() {
+                this.left = Self::delete(left, target);
+            }
+            return Some(Self::rebalance(this));
+        }
+
+        if target > &this.value {
+            if let Some(right) = this.right.take() {
+                this.right = Self::delete(right, target);
+            }
+            return Some(Self::rebalance(this));
+        }
+
+        match (this.left.take(), this.right.take()) {
+            (None, None) => None,
+            (Some(left), None) => Some(left),
+            (None, Some(right)) => Some(right),
+            (Some(left), Some(right)) => {
+                // the successor takes the place of the deleted node
+                let (right, mut successor) = Self::remove_min(right);
+                successor.left = Some(left);
+                successor.right = right;
+                Some(Self::rebalance(successor))
+            }
+        }
+    }
+
+    fn depth_first_search(&self, value: &T) -> bool {
+        if self.value == *value {
+            return true;
+        }
+        let next: &Option<Box<Node<T>>> = if *value < self.value { &self.left } else { &self.right };
+        match next.as_ref() {
+            None => false,
+            Some(t) => t.depth_first_search(value),
+        }
+    }
+
+    fn check(&self, min: Option<&T>, max: Option<&T>) -> Result<usize, String> {
+        // checks the subtree and returns its real height. All its values must be between min and max
+        if min.is_some_and(|min| self.value < *min) || max.is_some_and(|max| self.value > *max) {
+            return Err("the values are out of order".to_owned());
+        }
+        let left: usize = match self.left.as_ref() {
+            None => 0,
+            Some(t) => t.check(min, Some(&self.value))?,
+        };
+        let right: usize = match self.right.as_ref() {
+            None => 0,
+            Some(t) => t.check(Some(&self.value), max)?,
+        };
+        if left.abs_diff(right) > 1 {
+            return Err("a node is unbalanced".to_owned());
+        }
+        if self.height != 1 + left.max(right) {
+            return Err("a node has a wrong height".to_owned());
+        }
+        Ok(self.height)
+    }
+}
+
+impl<T> SearchNode<T> for Node<T> {
+    fn value(&self) -> &T {
+        &self.value
+    }
+
+    fn left(&self) -> Option<&Node<T>> {
+        self.left.as_deref()
+    }
+
+    fn right(&self) -> Option<&Node<T>> {
+        self.right.as_deref()
+    }
+}
+
+pub struct AvlTree<T> {
+    root: Option<Box<Node<T>>>,
+    len: usize,
+}
+
+impl<T: PartialOrd> AvlTree<T> {
+    pub fn new() -> AvlTree<T> {
+        AvlTree {
+            root: None,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn height(&self) -> usize {
+        // 0 for an empty tree
+        height(&self.root)
+    }
+
+    pub fn pre_order_traverse<F: FnMut(&T)>(&self, visit_func: &mut F) {
+        orderedset::pre_order_traverse(self.root.as_deref(), visit_func);
+    }
+
+    pub fn post_order_traverse<F: FnMut(&T)>(&self, visit_func: &mut F) {
+        orderedset::post_order_traverse(self.root.as_deref(), visit_func);
+    }
+
+    pub fn in_order_traverse<F: FnMut(&T)>(&self, visit_func: &mut F) {
+        orderedset::in_order_traverse(self.root.as_deref(), visit_func);
+    }
+
+    pub fn depth_first_search(&self, value: &T) -> bool {
+        if let Some(root) = self.root.as_ref() {
+            return root.depth_first_search(value);
+        }
+        false
+    }
+
+    pub fn insert_ordered(&mut self, value: T) {
+        self.root = Some(Node::insert(self.root.take(), value));
+        self.len += 1;
+    }
+
+    pub fn delete(&mut self, value: &T) {
+        // deletes one occurrence of the value, if there's any
+        if !self.depth_first_search(value) {
+            return;
+        }
+        if let Some(root) = self.root.take() {
+            self.root = Node::delete(root, value);
+            self.len -= 1;
+        }
+    }
+
+    pub fn check_invariants(&self) -> Result<(), String> {
+        // the values are ordered, every node is balanced and its stored height is right
+        if let Some(root) = self.root.as_ref() {
+            root.check(None, None)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::next_random;
+
+    fn in_order(tree: &AvlTree<i32>) -> Vec<i32> {
+        let mut path: Vec<i32> = vec![];
+        tree.in_order_traverse(&mut (|value: &i32| path.push(*value)));
+        path
+    }
+
+    #[test]
+    fn avl_rotations() {
+        let mut tree: AvlTree<i32> = AvlTree::new();
+        assert_eq!(0, tree.height());
+
+        // right-right, left-left, left-right and right-left cases
+        for value in [10, 20, 30, 5, 1, 3, 40, 35] {
+            tree.insert_ordered(value);
+            tree.check_invariants().unwrap();
+        }
+        let mut pre_order_path: Vec<i32> = vec![];
+        tree.pre_order_traverse(&mut (|value: &i32| pre_order_path.push(*value)));
+        assert_eq!(vec![5, 1, 3, 20, 10, 35, 30, 40], pre_order_path);
+        assert_eq!(4, tree.height());
+
+        tree.delete(&20);
+        tree.delete(&1);
+        tree.delete(&99);
+        tree.check_invariants().unwrap();
+        assert_eq!(vec![3, 5, 10, 30, 35, 40], in_order(&tree));
+        assert_eq!(6, tree.len());
+        assert!(tree.depth_first_search(&35));
+        assert!(!tree.depth_first_search(&20));
+    }
+
+    #[test]
+    fn avl_owned_values() {
+        let mut tree: AvlTree<String> = AvlTree::new();
+        for value in ["m", "c", "x", "a", "e"] {
+            tree.insert_ordered(value.to_owned());
+        }
+        tree.delete(&"x".to_owned());
+        tree.check_invariants().unwrap();
+        assert!(tree.depth_first_search(&"e".to_owned()));
+        assert!(!tree.depth_first_search(&"x".to_owned()));
+
+        let mut post_order_path: Vec<String> = vec![];
+        tree.post_order_traverse(&mut (|value: &String| post_order_path.push(value.clone())));
+        assert_eq!(vec!["a", "e", "m", "c"], post_order_path);
+    }
+
+    #[test]
+    fn avl_sorted_insertions() {
+        // a plain BinaryTree would be a list of 100000 nodes
+        let mut tree: AvlTree<i32> = AvlTree::new();
+        for value in 0..100_000 {
+            tree.insert_ordered(value);
+        }
+        tree.check_invariants().unwrap();
+        assert_eq!(17, tree.height());
+        assert!(tree.depth_first_search(&99_999));
+
+        for value in 0..50_000 {
+            tree.delete(&value);
+        }
+        tree.check_invariants().unwrap();
+        assert_eq!(50_000, tree.len());
+        assert!(tree.height() <= 17);
+    }
+
+    #[test]
+    fn avl_against_sorted_vec() {
+        let mut seed: u64 = 13;
+        let mut tree: AvlTree<i32> = AvlTree::new();
+        let mut sorted: Vec<i32> = vec![];
+        for _ in 0..2000 {
+            let value: i32 = (next_random(&mut seed) % 200) as i32;
+            if next_random(&mut seed).is_multiple_of(3) {
+                tree.delete(&value);
+                if let Ok(i) = sorted.binary_search(&value) {
+                    sorted.remove(i);
+                }
+            } else {
+                tree.insert_ordered(value);
+                let i: usize = sorted.partition_point(|e| *e < value);
+                sorted.insert(i, value);
+            }
+            tree.check_invariants().unwrap();
+        }
+        assert_eq!(sorted, in_order(&tree));
+        assert_eq!(sorted.len(), tree.len());
+        // an AVL tree is at most about 1.44 times as tall as a perfectly balanced one
+        assert!((tree.height() as f64) <= 1.44 * ((sorted.len() + 2) as f64).log2());
+    }
+}
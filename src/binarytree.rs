@@ -1,6 +1,9 @@
 #![allow(dead_code)]
 
-use std::{ collections::VecDeque, ops::Deref };
+use std::{ cmp::Ordering, collections::VecDeque, fmt::{ self, Display }, mem, ptr, str::FromStr };
+use std::ops::{ Bound, Deref, RangeBounds, RangeFull };
+
+use crate::{ dot, errordsa, orderedset::{ self, OrderedSet, SearchNode } };
 
 enum BranchSide {
     Left,
@@ -11,17 +14,47 @@ struct Node<T> {
     value: T,
     left: Option<Box<Node<T>>>,
     right: Option<Box<Node<T>>>,
+    size: usize, // amount of nodes in the subtree, kept up to date by BinaryTree (BstMap doesn't use it)
+}
+
+fn size<T>(node: &Option<Box<Node<T>>>) -> usize {
+    node.as_ref().map_or(0, |n| n.size)
 }
 
-impl<T: PartialEq + PartialOrd + Copy> Node<T> {
+fn level_order<T>(root: Option<&Node<T>>) -> Vec<Option<&T>> {
+    // the values level by level, with None for the missing children of the nodes, except at the end
+    let mut level_order: Vec<Option<&T>> = vec![];
+    let mut queue: VecDeque<Option<&Node<T>>> = VecDeque::from([root]);
+    while let Some(node) = queue.pop_front() {
+        match node {
+            None => level_order.push(None),
+            Some(node) => {
+                level_order.push(Some(&node.value));
+                queue.push_back(node.left.as_deref());
+                queue.push_back(node.right.as_deref());
+            }
+        }
+    }
+    while level_order.last().is_some_and(|value| value.is_none()) {
+        level_order.pop();
+    }
+    level_order
+}
+
+impl<T> Node<T> {
     fn new(value: T) -> Node<T> {
         Node {
             value,
             left: None,
             right: None,
+            size: 1,
         }
     }
 
+    fn update_size(&mut self) {
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+
     fn pre_order_traverse<F: FnMut(&T)>(&self, visit_func: &mut F) {
         // these traversals are basically an example of Depth First Search
         visit_func(&self.value);
@@ -59,6 +92,63 @@ impl<T: PartialEq + PartialOrd + Copy> Node<T> {
         }
     }
 
+    fn node_insert(&mut self, value: T, branch: BranchSide) {
+        // If the branch exists, it is overwritten
+        match branch {
+            BranchSide::Left => {
+                self.left = Some(Box::new(Node::new(value)));
+            }
+            BranchSide::Right => {
+                self.right = Some(Box::new(Node::new(value)));
+            }
+        }
+        self.update_size();
+    }
+
+    fn join_children(&mut self) -> Option<Box<Node<T>>> {
+        // takes the children of the node, and returns the subtree that takes its place once it's removed
+        match (self.left.take(), self.right.take()) {
+            (None, None) => None,
+            (Some(left), None) => Some(left),
+            (None, Some(right)) => Some(right),
+            (Some(mut left), Some(right)) => {
+                if let Some(mut rightmost) = left.rightmost_child() {
+                    rightmost.left = Some(left);
+                    rightmost.right = Some(right);
+                    rightmost.update_size();
+                    Some(rightmost)
+                } else {
+                    left.right = Some(right);
+                    left.update_size();
+                    Some(left)
+                }
+            }
+        }
+    }
+
+    //  Returns the rightmost child, unless the node itself is that child.
+    fn rightmost_child(&mut self) -> Option<Box<Node<T>>> {
+        match self.right.as_mut() {
+            Some(right) => {
+                let r = if let Some(t) = right.rightmost_child() {
+                    Some(t)
+                } else {
+                    let mut r = self.right.take();
+                    if let Some(ref mut r) = r {
+                        self.right = std::mem::replace(&mut r.left, None);
+                        r.update_size();
+                    }
+                    r
+                };
+                self.update_size();
+                r
+            }
+            None => None,
+        }
+    }
+}
+
+impl<T: PartialEq + PartialOrd> Node<T> {
     fn breadth_first_search(&self, value: T) -> bool {
         // for unordered trees
         let mut queue: VecDeque<&Node<T>> = VecDeque::from([self]);
@@ -100,18 +190,6 @@ impl<T: PartialEq + PartialOrd + Copy> Node<T> {
         return check_left && check_right;
     }
 
-    fn node_insert(&mut self, value: T, branch: BranchSide) {
-        // If the branch exists, it is overwritten
-        match branch {
-            BranchSide::Left => {
-                self.left = Some(Box::new(Node::new(value)));
-            }
-            BranchSide::Right => {
-                self.right = Some(Box::new(Node::new(value)));
-            }
-        }
-    }
-
     fn depth_first_search(&self, value: T) -> bool {
         // tree must be ordered. Left branch has smaller or equal values and Right has bigger values
         if self.value == value {
@@ -130,12 +208,16 @@ impl<T: PartialEq + PartialOrd + Copy> Node<T> {
                 None => { self.node_insert(value, BranchSide::Left) }
                 Some(t) => {
                     t.insert_ordered(value);
+                    self.update_size();
                 }
             }
         } else if value > self.value {
             match self.right.as_mut() {
                 None => { self.node_insert(value, BranchSide::Right) }
-                Some(t) => { t.insert_ordered(value) }
+                Some(t) => {
+                    t.insert_ordered(value);
+                    self.update_size();
+                }
             }
         }
     }
@@ -146,6 +228,7 @@ impl<T: PartialEq + PartialOrd + Copy> Node<T> {
             if let Some(left) = this.left.take() {
                 this.left = Self::delete(left, target);
             }
+            this.update_size();
             return Some(this);
         }
 
@@ -153,42 +236,40 @@ impl<T: PartialEq + PartialOrd + Copy> Node<T> {
             if let Some(right) = this.right.take() {
                 this.right = Self::delete(right, target);
             }
+            this.update_size();
             return Some(this);
         }
 
-        match (this.left.take(), this.right.take()) {
-            (None, None) => None,
-            (Some(left), None) => Some(left),
-            (None, Some(right)) => Some(right),
-            (Some(mut left), Some(right)) => {
-                if let Some(mut rightmost) = left.rightmost_child() {
-                    rightmost.left = Some(left);
-                    rightmost.right = Some(right);
-                    Some(rightmost)
-                } else {
-                    left.right = Some(right);
-                    Some(left)
-                }
-            }
-        }
+        this.join_children()
     }
+}
 
-    //  Returns the rightmost child, unless the node itself is that child.
-    fn rightmost_child(&mut self) -> Option<Box<Node<T>>> {
-        match self.right.as_mut() {
-            Some(right) => {
-                if let Some(t) = right.rightmost_child() {
-                    Some(t)
-                } else {
-                    let mut r = self.right.take();
-                    if let Some(ref mut r) = r {
-                        self.right = std::mem::replace(&mut r.left, None);
-                    }
-                    r
-                }
-            }
-            None => None,
-        }
+impl<T: PartialEq + Clone> Node<T> {
+    fn from_pre_order_and_in_order(pre_order: &[T], in_order: &[T]) -> Result<Option<Box<Node<T>>>, errordsa::Error> {
+        // the first value of the pre-order is the root. It splits the in-order in the left and right subtrees, whose
+        // pre-orders come right after the root, in the same order
+        let Some((value, rest)) = pre_order.split_first() else {
+            return Ok(None);
+        };
+        let i: usize = in_order.iter().position(|v| v == value).ok_or(errordsa::Error::InconsistentTraversals)?;
+        let mut node: Box<Node<T>> = Box::new(Node::new(value.clone()));
+        node.left = Self::from_pre_order_and_in_order(&rest[..i], &in_order[..i])?;
+        node.right = Self::from_pre_order_and_in_order(&rest[i..], &in_order[i + 1..])?;
+        node.update_size();
+        Ok(Some(node))
+    }
+
+    fn from_post_order_and_in_order(post_order: &[T], in_order: &[T]) -> Result<Option<Box<Node<T>>>, errordsa::Error> {
+        // the same, but the root is the last value and the subtrees come before it
+        let Some((value, rest)) = post_order.split_last() else {
+            return Ok(None);
+        };
+        let i: usize = in_order.iter().position(|v| v == value).ok_or(errordsa::Error::InconsistentTraversals)?;
+        let mut node: Box<Node<T>> = Box::new(Node::new(value.clone()));
+        node.left = Self::from_post_order_and_in_order(&rest[..i], &in_order[..i])?;
+        node.right = Self::from_post_order_and_in_order(&rest[i..], &in_order[i + 1..])?;
+        node.update_size();
+        Ok(Some(node))
     }
 }
 
@@ -196,7 +277,7 @@ pub struct BinaryTree<T> {
     root: Option<Box<Node<T>>>,
 }
 
-impl<T: PartialEq + PartialOrd + Copy> BinaryTree<T> {
+impl<T: PartialEq + PartialOrd> BinaryTree<T> {
     pub fn new() -> BinaryTree<T> {
         BinaryTree {
             root: None,
@@ -259,11 +340,723 @@ impl<T: PartialEq + PartialOrd + Copy> BinaryTree<T> {
             self.root = Node::delete(root, &value);
         }
     }
+
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    pub fn select(&self, k: usize) -> Option<&T> {
+        // the k-th smallest value, starting from 0
+        let mut k: usize = k;
+        let mut current: Option<&Box<Node<T>>> = self.root.as_ref();
+        while let Some(node) = current {
+            let left: usize = size(&node.left);
+            match k.cmp(&left) {
+                Ordering::Less => {
+                    current = node.left.as_ref();
+                }
+                Ordering::Equal => {
+                    return Some(&node.value);
+                }
+                Ordering::Greater => {
+                    k -= left + 1;
+                    current = node.right.as_ref();
+                }
+            }
+        }
+        None
+    }
+
+    pub fn rank(&self, value: &T) -> usize {
+        // amount of values smaller than value
+        let mut rank: usize = 0;
+        let mut current: Option<&Box<Node<T>>> = self.root.as_ref();
+        while let Some(node) = current {
+            if *value <= node.value {
+                current = node.left.as_ref();
+            } else {
+                // the node and its whole left subtree are smaller
+                rank += size(&node.left) + 1;
+                current = node.right.as_ref();
+            }
+        }
+        rank
+    }
+
+    pub fn count_in_range(&self, a: &T, b: &T) -> usize {
+        // amount of values in a..b (including a but not b), like range(a..b).count() but in O(height)
+        self.rank(b).saturating_sub(self.rank(a))
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        // in order, from the smallest value. It can also be walked backwards with rev() or next_back()
+        self.range(..)
+    }
+
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> Range<'_, T, R> {
+        // the values inside the range, in order. The stacks start with the paths to the first and last values in it
+        let mut front: Vec<&Node<T>> = vec![];
+        let mut current: Option<&Box<Node<T>>> = self.root.as_ref();
+        while let Some(node) = current {
+            if after_start(&range, &node.value) {
+                front.push(node);
+                current = node.left.as_ref();
+            } else {
+                current = node.right.as_ref();
+            }
+        }
+
+        let mut back: Vec<&Node<T>> = vec![];
+        let mut current: Option<&Box<Node<T>>> = self.root.as_ref();
+        while let Some(node) = current {
+            if before_end(&range, &node.value) {
+                back.push(node);
+                current = node.right.as_ref();
+            } else {
+                current = node.left.as_ref();
+            }
+        }
+
+        Range { front, back, front_last: None, back_last: None, range }
+    }
+
+    pub fn min(&self) -> Option<&T> {
+        orderedset::min(self.root.as_deref())
+    }
+
+    pub fn max(&self) -> Option<&T> {
+        orderedset::max(self.root.as_deref())
+    }
+
+    pub fn floor(&self, value: &T) -> Option<&T> {
+        // the biggest value that is smaller than or equal to value
+        orderedset::last_where(self.root.as_deref(), |v| v <= value)
+    }
+
+    pub fn ceiling(&self, value: &T) -> Option<&T> {
+        // the smallest value that is bigger than or equal to value
+        orderedset::first_where(self.root.as_deref(), |v| v >= value)
+    }
+
+    pub fn predecessor(&self, value: &T) -> Option<&T> {
+        // the biggest value that is smaller than value
+        orderedset::last_where(self.root.as_deref(), |v| v < value)
+    }
+
+    pub fn successor(&self, value: &T) -> Option<&T> {
+        // the smallest value that is bigger than value
+        orderedset::first_where(self.root.as_deref(), |v| v > value)
+    }
+}
+
+// Constructors for trees described by their traversals, as in exercises and test fixtures. The trees don't need to
+// be ordered, so they can be checked with the traversals and breadth_first_search, but not with the ordered queries.
+// The values must be distinct for the pre-order and post-order ones: with repeated values there may be several trees
+// with the same traversals, and the first match in the in-order is taken.
+
+impl<T: PartialEq + Clone> BinaryTree<T> {
+    pub fn from_pre_order_and_in_order(pre_order: &[T], in_order: &[T]) -> Result<BinaryTree<T>, errordsa::Error> {
+        if pre_order.len() != in_order.len() {
+            return Err(errordsa::Error::TraversalLengthMismatch);
+        }
+        Ok(BinaryTree { root: Node::from_pre_order_and_in_order(pre_order, in_order)? })
+    }
+
+    pub fn from_post_order_and_in_order(post_order: &[T], in_order: &[T]) -> Result<BinaryTree<T>, errordsa::Error> {
+        if post_order.len() != in_order.len() {
+            return Err(errordsa::Error::TraversalLengthMismatch);
+        }
+        Ok(BinaryTree { root: Node::from_post_order_and_in_order(post_order, in_order)? })
+    }
+
+    pub fn from_level_order(level_order: &[Option<T>]) -> Result<BinaryTree<T>, errordsa::Error> {
+        // the nodes level by level, with None for a missing child: [1, 2, None, 3] is 1 with a left child 2, which
+        // has a left child 3. The children of missing nodes aren't listed, so the k-th node present (counting from
+        // 0) has its children at 2k + 1 and 2k + 2. An entry that isn't the child of any node is an error
+        let mut present: usize = 0; // amount of nodes before the current entry
+        for (i, value) in level_order.iter().enumerate() {
+            if (i == 0 && value.is_none() && level_order.len() > 1) || (i > 0 && (i - 1) / 2 >= present) {
+                return Err(errordsa::Error::InconsistentTraversals);
+            }
+            if value.is_some() {
+                present += 1;
+            }
+        }
+
+        // the children come after their parents, so the nodes are built from the last one
+        let mut nodes: Vec<Option<Box<Node<T>>>> = (0..level_order.len()).map(|_| None).collect();
+        for (i, value) in level_order.iter().enumerate().rev() {
+            let Some(value) = value else {
+                continue;
+            };
+            present -= 1;
+            let mut node: Box<Node<T>> = Box::new(Node::new(value.clone()));
+            node.left = nodes.get_mut(2 * present + 1).and_then(|child| child.take());
+            node.right = nodes.get_mut(2 * present + 2).and_then(|child| child.take());
+            node.update_size();
+            nodes[i] = Some(node);
+        }
+        Ok(BinaryTree { root: nodes.into_iter().next().flatten() })
+    }
+
+    pub fn to_level_order(&self) -> Vec<Option<T>> {
+        // the inverse of from_level_order, without Nones at the end
+        level_order(self.root.as_deref()).into_iter().map(|value| value.cloned()).collect()
+    }
+}
+
+// Text formats, to keep trees in fixture files. Both can be read back: the level order used by LeetCode,
+// "[1,2,null,3]" (see from_level_order), and S-expressions, "(1 (2 (3) ()) ())", where a node is (value left right),
+// a leaf can be written as (value) and an empty tree is (). The values are written with Display and read with
+// FromStr, so they can't contain commas, parentheses or whitespace. FromStr for the tree takes either format.
+
+impl<T: Display> BinaryTree<T> {
+    pub fn to_level_order_string(&self) -> String {
+        let values: Vec<String> = level_order(self.root.as_deref())
+            .into_iter()
+            .map(|value| value.map_or("null".to_owned(), |v| v.to_string()))
+            .collect();
+        format!("[{}]", values.join(","))
+    }
+
+    pub fn to_sexpr(&self) -> String {
+        fn write<T: Display>(node: Option<&Node<T>>, sexpr: &mut String) {
+            match node {
+                None => sexpr.push_str("()"),
+                Some(node) if node.left.is_none() && node.right.is_none() => {
+                    sexpr.push_str(&format!("({})", node.value));
+                }
+                Some(node) => {
+                    sexpr.push_str(&format!("({} ", node.value));
+                    write(node.left.as_deref(), sexpr);
+                    sexpr.push(' ');
+                    write(node.right.as_deref(), sexpr);
+                    sexpr.push(')');
+                }
+            }
+        }
+        let mut sexpr: String = String::new();
+        write(self.root.as_deref(), &mut sexpr);
+        sexpr
+    }
+
+    pub fn to_dot(&self, path: &[T]) -> String where T: PartialEq {
+        // Graphviz DOT text, with the edges marked L or R. path is a walk down from the root, like the one followed
+        // by a search (see dot::tree)
+        dot::tree(
+            self.root.as_deref(),
+            |node| &node.value,
+            |node| {
+                [("L", node.left.as_deref()), ("R", node.right.as_deref())]
+                    .into_iter()
+                    .filter_map(|(side, child)| Some((Some(side), child?)))
+            },
+            path
+        )
+    }
+}
+
+impl<T: PartialEq + Clone + FromStr> BinaryTree<T> {
+    pub fn parse_level_order(s: &str) -> Result<BinaryTree<T>, errordsa::Error> {
+        let inner: &str = s
+            .trim()
+            .strip_prefix('[')
+            .and_then(|s| s.strip_suffix(']'))
+            .ok_or(errordsa::Error::InvalidFormat)?;
+        if inner.trim().is_empty() {
+            return Ok(BinaryTree { root: None });
+        }
+        let mut values: Vec<Option<T>> = vec![];
+        for token in inner.split(',').map(|token| token.trim()) {
+            if token == "null" {
+                values.push(None);
+            } else {
+                values.push(Some(token.parse().map_err(|_| errordsa::Error::InvalidFormat)?));
+            }
+        }
+        BinaryTree::from_level_order(&values)
+    }
+
+    pub fn parse_sexpr(s: &str) -> Result<BinaryTree<T>, errordsa::Error> {
+        fn parse<T: FromStr>(tokens: &[&str], i: &mut usize) -> Result<Option<Box<Node<T>>>, errordsa::Error> {
+            let mut next = || {
+                let token: Option<&str> = tokens.get(*i).copied();
+                *i += 1;
+                token.ok_or(errordsa::Error::InvalidFormat)
+            };
+            if next()? != "(" {
+                return Err(errordsa::Error::InvalidFormat);
+            }
+            let value: T = match next()? {
+                ")" => return Ok(None),
+                "(" => return Err(errordsa::Error::InvalidFormat),
+                token => token.parse().map_err(|_| errordsa::Error::InvalidFormat)?,
+            };
+            let mut node: Box<Node<T>> = Box::new(Node::new(value));
+            if tokens.get(*i) == Some(&")") {
+                *i += 1;
+                return Ok(Some(node));
+            }
+            node.left = parse(tokens, i)?;
+            node.right = parse(tokens, i)?;
+            if tokens.get(*i) != Some(&")") {
+                return Err(errordsa::Error::InvalidFormat);
+            }
+            *i += 1;
+            node.update_size();
+            Ok(Some(node))
+        }
+
+        // the tokens are the parentheses and the values between them
+        let mut tokens: Vec<&str> = vec![];
+        let mut start: Option<usize> = None;
+        for (i, c) in s.char_indices() {
+            if c == '(' || c == ')' || c.is_whitespace() {
+                if let Some(start) = start.take() {
+                    tokens.push(&s[start..i]);
+                }
+                if !c.is_whitespace() {
+                    tokens.push(&s[i..i + 1]);
+                }
+            } else if start.is_none() {
+                start = Some(i);
+            }
+        }
+        if let Some(start) = start {
+            tokens.push(&s[start..]);
+        }
+
+        let mut i: usize = 0;
+        let root: Option<Box<Node<T>>> = parse(&tokens, &mut i)?;
+        if i != tokens.len() {
+            return Err(errordsa::Error::InvalidFormat);
+        }
+        Ok(BinaryTree { root })
+    }
+}
+
+impl<T: PartialEq + Clone + FromStr> FromStr for BinaryTree<T> {
+    type Err = errordsa::Error;
+
+    fn from_str(s: &str) -> Result<BinaryTree<T>, errordsa::Error> {
+        if s.trim_start().starts_with('[') {
+            BinaryTree::parse_level_order(s)
+        } else {
+            BinaryTree::parse_sexpr(s)
+        }
+    }
+}
+
+impl<T: Display> Display for BinaryTree<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // one node per line under its parent, with L or R to tell the side when there's a single child:
+        // 1
+        // |-- L: 2
+        // |   `-- R: 3
+        // `-- R: 4
+        fn fmt_children<T: Display>(node: &Node<T>, prefix: &str, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let children: Vec<(&str, &Node<T>)> = [("L", node.left.as_deref()), ("R", node.right.as_deref())]
+                .into_iter()
+                .filter_map(|(side, child)| child.map(|child| (side, child)))
+                .collect();
+            for (i, (side, child)) in children.iter().enumerate() {
+                let last: bool = i + 1 == children.len();
+                write!(f, "\n{}{}{}: {}", prefix, if last { "`-- " } else { "|-- " }, side, child.value)?;
+                fmt_children(child, &format!("{}{}", prefix, if last { "    " } else { "|   " }), f)?;
+            }
+            Ok(())
+        }
+        match self.root.as_deref() {
+            None => write!(f, "(empty)"),
+            Some(root) => {
+                write!(f, "{}", root.value)?;
+                fmt_children(root, "", f)
+            }
+        }
+    }
+}
+
+impl<T: PartialEq + PartialOrd> Default for BinaryTree<T> {
+    fn default() -> BinaryTree<T> {
+        BinaryTree::new()
+    }
+}
+
+impl<T> SearchNode<T> for Node<T> {
+    fn value(&self) -> &T {
+        &self.value
+    }
+
+    fn left(&self) -> Option<&Node<T>> {
+        self.left.as_deref()
+    }
+
+    fn right(&self) -> Option<&Node<T>> {
+        self.right.as_deref()
+    }
+}
+
+impl<T: PartialEq + PartialOrd> OrderedSet<T> for BinaryTree<T> {
+    fn insert(&mut self, value: T) -> bool {
+        if OrderedSet::contains(self, &value) {
+            return false;
+        }
+        self.insert_ordered(value);
+        true
+    }
+
+    fn remove(&mut self, value: &T) -> bool {
+        if !OrderedSet::contains(self, value) {
+            return false;
+        }
+        if let Some(root) = self.root.take() {
+            self.root = Node::delete(root, value);
+        }
+        true
+    }
+
+    fn contains(&mut self, value: &T) -> bool {
+        self.floor(value) == Some(value)
+    }
+
+    fn len(&self) -> usize {
+        BinaryTree::len(self)
+    }
+
+    fn min(&mut self) -> Option<&T> {
+        BinaryTree::min(self)
+    }
+
+    fn max(&mut self) -> Option<&T> {
+        BinaryTree::max(self)
+    }
+
+    fn floor(&mut self, value: &T) -> Option<&T> {
+        BinaryTree::floor(self, value)
+    }
+
+    fn ceiling(&mut self, value: &T) -> Option<&T> {
+        BinaryTree::ceiling(self, value)
+    }
+
+    fn predecessor(&mut self, value: &T) -> Option<&T> {
+        BinaryTree::predecessor(self, value)
+    }
+
+    fn successor(&mut self, value: &T) -> Option<&T> {
+        BinaryTree::successor(self, value)
+    }
+
+    fn in_order_traverse<F: FnMut(&T)>(&self, visit_func: &mut F) {
+        BinaryTree::in_order_traverse(self, visit_func);
+    }
+}
+
+fn after_start<T: PartialOrd, R: RangeBounds<T>>(range: &R, value: &T) -> bool {
+    match range.start_bound() {
+        Bound::Included(start) => value >= start,
+        Bound::Excluded(start) => value > start,
+        Bound::Unbounded => true,
+    }
+}
+
+fn before_end<T: PartialOrd, R: RangeBounds<T>>(range: &R, value: &T) -> bool {
+    match range.end_bound() {
+        Bound::Included(end) => value <= end,
+        Bound::Excluded(end) => value < end,
+        Bound::Unbounded => true,
+    }
+}
+
+// The front stack has the next node and the ancestors that come after it, and the back stack the other way around.
+// When one of the ends reaches a node that the other end already returned, they have crossed and the iteration ends.
+
+pub struct Range<'a, T, R> {
+    front: Vec<&'a Node<T>>,
+    back: Vec<&'a Node<T>>,
+    front_last: Option<&'a Node<T>>, // the last node returned by next
+    back_last: Option<&'a Node<T>>, // the last node returned by next_back
+    range: R,
+}
+
+pub type Iter<'a, T> = Range<'a, T, RangeFull>;
+
+impl<'a, T: PartialOrd, R: RangeBounds<T>> Iterator for Range<'a, T, R> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node: &'a Node<T> = self.front.pop()?;
+        if self.back_last.is_some_and(|last| ptr::eq(last, node)) || !before_end(&self.range, &node.value) {
+            self.front.clear();
+            self.back.clear();
+            return None;
+        }
+        // the values that come right after this one are the leftmost path of its right subtree
+        let mut current: Option<&'a Box<Node<T>>> = node.right.as_ref();
+        while let Some(n) = current {
+            self.front.push(n);
+            current = n.left.as_ref();
+        }
+        self.front_last = Some(node);
+        Some(&node.value)
+    }
+}
+
+impl<'a, T: PartialOrd, R: RangeBounds<T>> DoubleEndedIterator for Range<'a, T, R> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        let node: &'a Node<T> = self.back.pop()?;
+        if self.front_last.is_some_and(|last| ptr::eq(last, node)) || !after_start(&self.range, &node.value) {
+            self.front.clear();
+            self.back.clear();
+            return None;
+        }
+        let mut current: Option<&'a Box<Node<T>>> = node.left.as_ref();
+        while let Some(n) = current {
+            self.back.push(n);
+            current = n.right.as_ref();
+        }
+        self.back_last = Some(node);
+        Some(&node.value)
+    }
+}
+
+impl<'a, T: PartialEq + PartialOrd> IntoIterator for &'a BinaryTree<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+pub struct IntoIter<T> {
+    stack: Vec<Box<Node<T>>>, // same as Range.front, but the nodes are owned and their left child was taken
+}
+
+impl<T> IntoIter<T> {
+    fn push_left_path(&mut self, link: Option<Box<Node<T>>>) {
+        let mut link: Option<Box<Node<T>>> = link;
+        while let Some(mut node) = link {
+            link = node.left.take();
+            self.stack.push(node);
+        }
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let mut node: Box<Node<T>> = self.stack.pop()?;
+        self.push_left_path(node.right.take());
+        Some(node.value)
+    }
+}
+
+impl<T> IntoIterator for BinaryTree<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        // in order, from the smallest value
+        let mut iter: IntoIter<T> = IntoIter { stack: vec![] };
+        iter.push_left_path(self.root);
+        iter
+    }
+}
+
+// Map on top of the same nodes: every node holds a (key, value) pair, and the tree is ordered by the keys only.
+// Unlike in BinaryTree, keys are unique, so inserting an existing key replaces its value.
+
+pub struct BstMap<K, V> {
+    root: Option<Box<Node<(K, V)>>>,
+    len: usize,
+}
+
+impl<K: Ord, V> BstMap<K, V> {
+    pub fn new() -> BstMap<K, V> {
+        BstMap {
+            root: None,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn find_link<'a>(root: &'a mut Option<Box<Node<(K, V)>>>, key: &K) -> &'a mut Option<Box<Node<(K, V)>>> {
+        // the link to the node of the key, or the empty one where it would be inserted
+        let mut link: &mut Option<Box<Node<(K, V)>>> = root;
+        while link.as_ref().is_some_and(|node| node.value.0 != *key) {
+            let node: &mut Box<Node<(K, V)>> = link.as_mut().unwrap();
+            link = if *key < node.value.0 { &mut node.left } else { &mut node.right };
+        }
+        link
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        // if the key was already there, its value is replaced and the old one is returned
+        match self.entry(key) {
+            Entry::Occupied(mut entry) => Some(entry.insert(value)),
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+                None
+            }
+        }
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let mut current: Option<&Box<Node<(K, V)>>> = self.root.as_ref();
+        while let Some(node) = current {
+            match key.cmp(&node.value.0) {
+                Ordering::Less => {
+                    current = node.left.as_ref();
+                }
+                Ordering::Greater => {
+                    current = node.right.as_ref();
+                }
+                Ordering::Equal => {
+                    return Some(&node.value.1);
+                }
+            }
+        }
+        None
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        Self::find_link(&mut self.root, key)
+            .as_mut()
+            .map(|node| &mut node.value.1)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let link: &mut Option<Box<Node<(K, V)>>> = Self::find_link(&mut self.root, key);
+        let mut node: Box<Node<(K, V)>> = link.take()?;
+        *link = node.join_children();
+        self.len -= 1;
+        Some(node.value.1)
+    }
+
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        // to insert or update a value with a single search
+        let link: &mut Option<Box<Node<(K, V)>>> = Self::find_link(&mut self.root, &key);
+        if link.is_some() {
+            Entry::Occupied(OccupiedEntry { node: link.as_mut().unwrap() })
+        } else {
+            Entry::Vacant(VacantEntry { key, link, len: &mut self.len })
+        }
+    }
+
+    pub fn in_order_traverse<F: FnMut(&K, &V)>(&self, visit_func: &mut F) {
+        if let Some(root) = self.root.as_ref() {
+            root.in_order_traverse(&mut |(key, value): &(K, V)| visit_func(key, value));
+        }
+    }
+}
+
+impl<K: Ord, V> Default for BstMap<K, V> {
+    fn default() -> BstMap<K, V> {
+        BstMap::new()
+    }
+}
+
+pub enum Entry<'a, K, V> {
+    Occupied(OccupiedEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+pub struct OccupiedEntry<'a, K, V> {
+    node: &'a mut Box<Node<(K, V)>>,
+}
+
+pub struct VacantEntry<'a, K, V> {
+    key: K,
+    link: &'a mut Option<Box<Node<(K, V)>>>,
+    len: &'a mut usize,
+}
+
+impl<'a, K, V> Entry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => &entry.key,
+        }
+    }
+
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    pub fn or_default(self) -> &'a mut V where V: Default {
+        self.or_insert_with(V::default)
+    }
+
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Entry<'a, K, V> {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, K, V> OccupiedEntry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        &self.node.value.0
+    }
+
+    pub fn get(&self) -> &V {
+        &self.node.value.1
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.node.value.1
+    }
+
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.node.value.1
+    }
+
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(&mut self.node.value.1, value)
+    }
+}
+
+impl<'a, K, V> VacantEntry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn insert(self, value: V) -> &'a mut V {
+        *self.len += 1;
+        let node: &mut Box<Node<(K, V)>> = self.link.insert(Box::new(Node::new((self.key, value))));
+        &mut node.value.1
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testutil::next_random;
 
     #[test]
     fn node_traverse() {
@@ -399,4 +1192,420 @@ mod tests {
         tree.pre_order_traverse(&mut (|value: &i32| pre_order_path.push(*value)));
         assert_eq!(vec![3, 2, 2, 11], pre_order_path);
     }
+
+    #[test]
+    fn bst_map() {
+        let mut map: BstMap<String, Vec<u32>> = BstMap::new();
+        assert_eq!(None, map.insert("pear".to_owned(), vec![1]));
+        assert_eq!(None, map.insert("apple".to_owned(), vec![2]));
+        assert_eq!(None, map.insert("fig".to_owned(), vec![3]));
+        assert_eq!(Some(vec![2]), map.insert("apple".to_owned(), vec![4]));
+        assert_eq!(3, map.len());
+
+        assert_eq!(Some(&vec![4]), map.get(&"apple".to_owned()));
+        map.get_mut(&"fig".to_owned()).unwrap().push(5);
+        assert!(map.contains_key(&"fig".to_owned()));
+        assert!(!map.contains_key(&"kiwi".to_owned()));
+
+        let mut keys: Vec<String> = vec![];
+        map.in_order_traverse(&mut (|key: &String, _: &Vec<u32>| keys.push(key.clone())));
+        assert_eq!(vec!["apple", "fig", "pear"], keys);
+
+        // pear is the root, and fig takes its place
+        assert_eq!(Some(vec![1]), map.remove(&"pear".to_owned()));
+        assert_eq!(None, map.remove(&"pear".to_owned()));
+        assert_eq!(Some(vec![4]), map.remove(&"apple".to_owned()));
+        assert_eq!(Some(&vec![3, 5]), map.get(&"fig".to_owned()));
+        assert_eq!(1, map.len());
+    }
+
+    #[test]
+    fn bst_map_entry() {
+        let mut counts: BstMap<char, usize> = BstMap::new();
+        for c in "hello world".chars() {
+            *counts.entry(c).or_insert(0) += 1;
+        }
+        assert_eq!(8, counts.len());
+        assert_eq!(Some(&3), counts.get(&'l'));
+
+        counts.entry('o').and_modify(|count| *count *= 10).or_default();
+        counts.entry('z').and_modify(|count| *count *= 10).or_default();
+        assert_eq!(Some(&20), counts.get(&'o'));
+        assert_eq!(Some(&0), counts.get(&'z'));
+        assert_eq!('q', *counts.entry('q').key());
+
+        match counts.entry('h') {
+            Entry::Occupied(mut entry) => {
+                assert_eq!(1, entry.insert(7));
+                assert_eq!(7, *entry.get());
+            }
+            Entry::Vacant(_) => panic!("h is in the map"),
+        }
+        assert_eq!(9, counts.len());
+    }
+
+    #[test]
+    fn btree_of_strings() {
+        let mut tree: BinaryTree<String> = BinaryTree::new();
+        tree.insert_ordered("pear".to_owned());
+        tree.insert_ordered("apple".to_owned());
+        tree.insert_ordered("fig".to_owned());
+        tree.delete("pear".to_owned());
+
+        assert!(tree.depth_first_search("fig".to_owned()));
+        assert!(!tree.depth_first_search("pear".to_owned()));
+    }
+
+    fn ordered_tree() -> BinaryTree<i32> {
+        let mut tree: BinaryTree<i32> = BinaryTree::new();
+        for value in [10, 2, 3, 2, 12, 14, 11, 7] {
+            tree.insert_ordered(value);
+        }
+        tree
+    }
+
+    #[test]
+    fn btree_iter() {
+        let tree: BinaryTree<i32> = ordered_tree();
+        assert_eq!(vec![&2, &2, &3, &7, &10, &11, &12, &14], tree.iter().collect::<Vec<&i32>>());
+        assert_eq!(vec![&14, &12, &11, &10, &7, &3, &2, &2], tree.iter().rev().collect::<Vec<&i32>>());
+        assert_eq!(Some(&7), tree.iter().find(|value| **value > 5));
+
+        // both ends meet in the middle
+        let mut iter: Iter<'_, i32> = tree.iter();
+        assert_eq!(Some(&2), iter.next());
+        assert_eq!(Some(&14), iter.next_back());
+        assert_eq!(Some(&12), iter.next_back());
+        assert_eq!(vec![&2, &3, &7, &10, &11], iter.by_ref().collect::<Vec<&i32>>());
+        assert_eq!(None, iter.next_back());
+
+        let mut sum: i32 = 0;
+        for value in &tree {
+            sum += value;
+        }
+        assert_eq!(61, sum);
+        assert_eq!(vec![2, 2, 3, 7, 10, 11, 12, 14], tree.into_iter().collect::<Vec<i32>>());
+        assert_eq!(None, BinaryTree::<i32>::new().iter().next());
+    }
+
+    #[test]
+    fn btree_range() {
+        let tree: BinaryTree<i32> = ordered_tree();
+        assert_eq!(vec![&3, &7, &10], tree.range(3..11).collect::<Vec<&i32>>());
+        assert_eq!(vec![&11, &10, &7, &3], tree.range(3..=11).rev().collect::<Vec<&i32>>());
+        assert_eq!(vec![&2, &2], tree.range(..3).collect::<Vec<&i32>>());
+        assert_eq!(vec![&12, &14], tree.range(12..).collect::<Vec<&i32>>());
+        assert_eq!(Vec::<&i32>::new(), tree.range(4..7).collect::<Vec<&i32>>());
+        assert_eq!(Vec::<&i32>::new(), tree.range(4..7).rev().collect::<Vec<&i32>>());
+
+        let mut range = tree.range(3..=12);
+        assert_eq!(Some(&3), range.next());
+        assert_eq!(Some(&12), range.next_back());
+        assert_eq!(vec![&7, &10, &11], range.collect::<Vec<&i32>>());
+    }
+
+    #[test]
+    fn btree_order_queries() {
+        let tree: BinaryTree<i32> = ordered_tree();
+        assert_eq!(Some(&2), tree.min());
+        assert_eq!(Some(&14), tree.max());
+
+        assert_eq!(Some(&7), tree.floor(&9));
+        assert_eq!(Some(&7), tree.floor(&7));
+        assert_eq!(None, tree.floor(&1));
+        assert_eq!(Some(&10), tree.ceiling(&9));
+        assert_eq!(Some(&10), tree.ceiling(&10));
+        assert_eq!(None, tree.ceiling(&15));
+
+        assert_eq!(Some(&3), tree.predecessor(&7));
+        assert_eq!(None, tree.predecessor(&2));
+        assert_eq!(Some(&11), tree.successor(&10));
+        assert_eq!(None, tree.successor(&14));
+
+        let empty: BinaryTree<i32> = BinaryTree::new();
+        assert_eq!(None, empty.min());
+        assert_eq!(None, empty.successor(&1));
+    }
+
+    fn check_sizes<T>(node: &Option<Box<Node<T>>>) -> usize {
+        // the real size of the subtree, checking the stored ones on the way
+        match node.as_ref() {
+            None => 0,
+            Some(n) => {
+                let real: usize = 1 + check_sizes(&n.left) + check_sizes(&n.right);
+                assert_eq!(real, n.size);
+                real
+            }
+        }
+    }
+
+    #[test]
+    fn btree_select_and_rank() {
+        let mut tree: BinaryTree<i32> = ordered_tree();
+        assert_eq!(8, tree.len());
+        assert_eq!(Some(&2), tree.select(0));
+        assert_eq!(Some(&2), tree.select(1));
+        assert_eq!(Some(&10), tree.select(4));
+        assert_eq!(Some(&14), tree.select(7));
+        assert_eq!(None, tree.select(8));
+
+        assert_eq!(0, tree.rank(&2));
+        assert_eq!(2, tree.rank(&3));
+        assert_eq!(4, tree.rank(&8));
+        assert_eq!(8, tree.rank(&20));
+        assert_eq!(3, tree.count_in_range(&3, &11));
+        assert_eq!(0, tree.count_in_range(&11, &3));
+
+        tree.delete(10);
+        tree.delete(4);
+        check_sizes(&tree.root);
+        assert_eq!(7, tree.len());
+        assert_eq!(Some(&11), tree.select(4));
+    }
+
+    #[test]
+    fn btree_order_statistics_against_sorted_vec() {
+        let mut seed: u64 = 19;
+        let mut tree: BinaryTree<u64> = BinaryTree::new();
+        let mut sorted: Vec<u64> = vec![];
+        for _ in 0..1000 {
+            let value: u64 = next_random(&mut seed) % 100;
+            if next_random(&mut seed).is_multiple_of(3) {
+                tree.delete(value);
+                if let Ok(i) = sorted.binary_search(&value) {
+                    sorted.remove(i);
+                }
+            } else {
+                tree.insert_ordered(value);
+                let i: usize = sorted.partition_point(|e| *e < value);
+                sorted.insert(i, value);
+            }
+            assert_eq!(sorted.len(), check_sizes(&tree.root));
+
+            let k: usize = (next_random(&mut seed) as usize) % (sorted.len() + 1);
+            assert_eq!(sorted.get(k), tree.select(k));
+            let x: u64 = next_random(&mut seed) % 100;
+            assert_eq!(sorted.partition_point(|e| *e < x), tree.rank(&x));
+            let y: u64 = x + next_random(&mut seed) % 20;
+            assert_eq!(sorted.iter().filter(|e| **e >= x && **e < y).count(), tree.count_in_range(&x, &y));
+        }
+    }
+
+    fn traversals<T: PartialEq + PartialOrd + Clone>(tree: &BinaryTree<T>) -> (Vec<T>, Vec<T>, Vec<T>) {
+        let mut pre_order: Vec<T> = vec![];
+        let mut in_order: Vec<T> = vec![];
+        let mut post_order: Vec<T> = vec![];
+        tree.pre_order_traverse(&mut (|value: &T| pre_order.push(value.clone())));
+        tree.in_order_traverse(&mut (|value: &T| in_order.push(value.clone())));
+        tree.post_order_traverse(&mut (|value: &T| post_order.push(value.clone())));
+        (pre_order, in_order, post_order)
+    }
+
+    #[test]
+    fn btree_from_traversals() {
+        //       1
+        //     /   \
+        //    2     3
+        //   / \   / \
+        //  4   5 6   7
+        let pre_order: Vec<i32> = vec![1, 2, 4, 5, 3, 6, 7];
+        let in_order: Vec<i32> = vec![4, 2, 5, 1, 6, 3, 7];
+        let post_order: Vec<i32> = vec![4, 5, 2, 6, 7, 3, 1];
+
+        let tree: BinaryTree<i32> = BinaryTree::from_pre_order_and_in_order(&pre_order, &in_order).unwrap();
+        assert_eq!((pre_order.clone(), in_order.clone(), post_order.clone()), traversals(&tree));
+        assert_eq!(7, check_sizes(&tree.root));
+        assert!(tree.breadth_first_search(6));
+
+        let tree: BinaryTree<i32> = BinaryTree::from_post_order_and_in_order(&post_order, &in_order).unwrap();
+        assert_eq!((pre_order.clone(), in_order.clone(), post_order.clone()), traversals(&tree));
+        assert_eq!(vec![Some(1), Some(2), Some(3), Some(4), Some(5), Some(6), Some(7)], tree.to_level_order());
+
+        // a path that goes left, then right
+        let tree: BinaryTree<i32> = BinaryTree::from_pre_order_and_in_order(&[1, 2, 3], &[2, 3, 1]).unwrap();
+        assert_eq!(vec![Some(1), Some(2), None, None, Some(3)], tree.to_level_order());
+
+        let tree: BinaryTree<i32> = BinaryTree::from_pre_order_and_in_order(&[], &[]).unwrap();
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn btree_from_inconsistent_traversals() {
+        assert_eq!(
+            Err(errordsa::Error::TraversalLengthMismatch),
+            BinaryTree::from_pre_order_and_in_order(&[1, 2], &[1]).map(|tree| tree.len())
+        );
+        assert_eq!(
+            Err(errordsa::Error::TraversalLengthMismatch),
+            BinaryTree::from_post_order_and_in_order(&[1], &[1, 2]).map(|tree| tree.len())
+        );
+        // a value that isn't in the in-order
+        assert_eq!(
+            Err(errordsa::Error::InconsistentTraversals),
+            BinaryTree::from_pre_order_and_in_order(&[1, 2, 3], &[1, 2, 4]).map(|tree| tree.len())
+        );
+        // 2 should be in the left subtree of 1, which only has 3
+        assert_eq!(
+            Err(errordsa::Error::InconsistentTraversals),
+            BinaryTree::from_pre_order_and_in_order(&[1, 2, 3], &[3, 1, 2]).map(|tree| tree.len())
+        );
+        assert_eq!(
+            Err(errordsa::Error::InconsistentTraversals),
+            BinaryTree::from_post_order_and_in_order(&[2, 3, 1], &[3, 1, 2]).map(|tree| tree.len())
+        );
+    }
+
+    #[test]
+    fn btree_from_level_order() {
+        let tree: BinaryTree<i32> = BinaryTree::from_level_order(&[Some(1), Some(2), None, Some(3)]).unwrap();
+        assert_eq!((vec![1, 2, 3], vec![3, 2, 1], vec![3, 2, 1]), traversals(&tree));
+        assert_eq!(3, check_sizes(&tree.root));
+        assert_eq!(vec![Some(1), Some(2), None, Some(3)], tree.to_level_order());
+
+        // the children of 2 aren't listed, so 4 and 5 are the children of 3
+        let level_order: Vec<Option<i32>> = vec![Some(1), Some(2), Some(3), None, None, Some(4), Some(5)];
+        let tree: BinaryTree<i32> = BinaryTree::from_level_order(&level_order).unwrap();
+        assert_eq!((vec![1, 2, 3, 4, 5], vec![2, 1, 4, 3, 5], vec![2, 4, 5, 3, 1]), traversals(&tree));
+        assert_eq!(level_order, tree.to_level_order());
+
+        // Nones at the end are allowed as long as they are children of some node
+        let tree: BinaryTree<i32> = BinaryTree::from_level_order(&[Some(1), None, None]).unwrap();
+        assert_eq!(vec![Some(1)], tree.to_level_order());
+        assert!(BinaryTree::<i32>::from_level_order(&[]).unwrap().is_empty());
+        assert!(BinaryTree::<i32>::from_level_order(&[None]).unwrap().is_empty());
+
+        let orphans: [&[Option<i32>]; 3] = [
+            &[None, Some(1)],
+            &[Some(1), None, None, Some(2)],
+            &[Some(1), None, None, None],
+        ];
+        for level_order in orphans {
+            assert_eq!(
+                Err(errordsa::Error::InconsistentTraversals),
+                BinaryTree::from_level_order(level_order).map(|tree| tree.len())
+            );
+        }
+    }
+
+    #[test]
+    fn btree_rebuilt_from_traversals() {
+        let mut seed: u64 = 23;
+        for _ in 0..20 {
+            // distinct values in a random order
+            let mut tree: BinaryTree<u64> = BinaryTree::new();
+            for _ in 0..200 {
+                let value: u64 = next_random(&mut seed) % 1000;
+                if !tree.depth_first_search(value) {
+                    tree.insert_ordered(value);
+                }
+            }
+            let (pre_order, in_order, post_order) = traversals(&tree);
+            let expected: Vec<Option<u64>> = tree.to_level_order();
+
+            let rebuilt: BinaryTree<u64> = BinaryTree::from_pre_order_and_in_order(&pre_order, &in_order).unwrap();
+            assert_eq!(expected, rebuilt.to_level_order());
+            let rebuilt: BinaryTree<u64> = BinaryTree::from_post_order_and_in_order(&post_order, &in_order).unwrap();
+            assert_eq!(expected, rebuilt.to_level_order());
+            let rebuilt: BinaryTree<u64> = BinaryTree::from_level_order(&expected).unwrap();
+            assert_eq!((pre_order.clone(), in_order.clone(), post_order.clone()), traversals(&rebuilt));
+            assert_eq!(tree.len(), check_sizes(&rebuilt.root));
+        }
+    }
+
+    #[test]
+    fn btree_text_formats() {
+        let tree: BinaryTree<i32> = BinaryTree::from_level_order(&[Some(1), Some(2), Some(4), None, Some(3)]).unwrap();
+        assert_eq!("[1,2,4,null,3]", tree.to_level_order_string());
+        assert_eq!("(1 (2 () (3)) (4))", tree.to_sexpr());
+        assert_eq!("1\n|-- L: 2\n|   `-- R: 3\n`-- R: 4", tree.to_string());
+
+        let texts: [&str; 4] = [
+            "[1,2,4,null,3]",
+            " [ 1, 2, 4, null, 3, null, null ] ",
+            "(1 (2 () (3 () ())) (4))",
+            "(1\n  (2 () (3))\n  (4))",
+        ];
+        for text in texts {
+            let parsed: BinaryTree<i32> = text.parse().unwrap();
+            assert_eq!(tree.to_level_order(), parsed.to_level_order());
+            assert_eq!(4, check_sizes(&parsed.root));
+        }
+
+        let empty: BinaryTree<i32> = BinaryTree::new();
+        assert_eq!("[]", empty.to_level_order_string());
+        assert_eq!("()", empty.to_sexpr());
+        assert_eq!("(empty)", empty.to_string());
+        assert!(BinaryTree::<i32>::parse_level_order("[]").unwrap().is_empty());
+        assert!(BinaryTree::<i32>::parse_sexpr("()").unwrap().is_empty());
+
+        let words: BinaryTree<String> = "(m (c) (x (p) ()))".parse().unwrap();
+        assert_eq!("[m,c,x,null,null,p]", words.to_level_order_string());
+        assert_eq!("m\n|-- L: c\n`-- R: x\n    `-- L: p", words.to_string());
+    }
+
+    #[test]
+    fn btree_parse_errors() {
+        let invalid: [&str; 10] = [
+            "1,2,3",
+            "[1,2,",
+            "[1,,2]",
+            "[1,two]",
+            "(1",
+            "(1 (2))",
+            "(1 () () ())",
+            "(1) (2)",
+            "((1))",
+            "1",
+        ];
+        for text in invalid {
+            let parsed: Result<BinaryTree<i32>, errordsa::Error> = text.parse();
+            assert_eq!(Err(errordsa::Error::InvalidFormat), parsed.map(|tree| tree.len()), "{}", text);
+        }
+        // well formed, but 2 isn't the child of any node
+        assert_eq!(
+            Err(errordsa::Error::InconsistentTraversals),
+            BinaryTree::<i32>::parse_level_order("[1,null,null,2]").map(|tree| tree.len())
+        );
+    }
+
+    #[test]
+    fn btree_text_round_trip() {
+        let mut seed: u64 = 29;
+        for _ in 0..20 {
+            let mut tree: BinaryTree<i64> = BinaryTree::new();
+            for _ in 0..100 {
+                tree.insert_ordered(next_random(&mut seed) as i64 % 100 - 50);
+            }
+            let expected: Vec<Option<i64>> = tree.to_level_order();
+            let parsed: BinaryTree<i64> = BinaryTree::parse_level_order(&tree.to_level_order_string()).unwrap();
+            assert_eq!(expected, parsed.to_level_order());
+            let parsed: BinaryTree<i64> = BinaryTree::parse_sexpr(&tree.to_sexpr()).unwrap();
+            assert_eq!(expected, parsed.to_level_order());
+            assert_eq!(tree.to_sexpr(), parsed.to_sexpr());
+        }
+    }
+
+    #[test]
+    fn btree_to_dot() {
+        let tree: BinaryTree<i32> = "(5 (3 (1) (4)) (8 () (9)))".parse().unwrap();
+        // the path followed when searching for 4
+        let expected: &str = "\
+digraph {
+    n0 [label=\"5\", color=red];
+    n0 -> n1 [label=\"L\", color=red, penwidth=2];
+    n1 [label=\"3\", color=red];
+    n1 -> n2 [label=\"L\"];
+    n2 [label=\"1\"];
+    n1 -> n3 [label=\"R\", color=red, penwidth=2];
+    n3 [label=\"4\", color=red];
+    n0 -> n4 [label=\"R\"];
+    n4 [label=\"8\"];
+    n4 -> n5 [label=\"R\"];
+    n5 [label=\"9\"];
+}
+";
+        assert_eq!(expected, tree.to_dot(&[5, 3, 4]));
+        assert!(!tree.to_dot(&[]).contains("red"));
+        assert_eq!("digraph {\n}\n", BinaryTree::<i32>::new().to_dot(&[5]));
+    }
 }
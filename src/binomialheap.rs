@@ -0,0 +1,216 @@
+#![allow(dead_code)]
+
+use crate::{ errordsa, mergeableheap::{ shift, Arena, Handle, PriorityQueue } };
+
+// A binomial heap is a list of binomial trees, at most one of each order. A tree of order k is two trees of order
+// k - 1 where one root became the leftmost child of the other, so it has 2^k nodes and its root has k children.
+// The list of trees works like a binary number: adding a tree of order k is like adding 2^k, and two trees of the
+// same order are linked into one of the next order, like carrying a digit. So there are at most log(n) trees.
+// Decreasing a key moves the value up its tree by swapping it with its parent's. Since the values move between
+// nodes, the handles point to a key, which knows in which node its value currently is.
+
+struct Node<T> {
+    value: T,
+    key: usize, // the key that points to this node
+    parent: Option<usize>,
+    child: Option<usize>, // leftmost child
+    sibling: Option<usize>, // right sibling
+    order: usize, // amount of children
+}
+
+pub struct BinomialHeap<T> {
+    nodes: Arena<Node<T>>,
+    keys: Arena<usize>, // node of every key
+    trees: Vec<Option<usize>>, // root of the tree of every order
+}
+
+impl<T: PartialOrd> BinomialHeap<T> {
+    pub fn new() -> BinomialHeap<T> {
+        BinomialHeap {
+            nodes: Arena::new(),
+            keys: Arena::new(),
+            trees: Vec::<Option<usize>>::new(),
+        }
+    }
+
+    fn link(&mut self, a: usize, b: usize) -> usize {
+        // the root with the bigger value becomes the leftmost child of the other one, which is returned
+        let (parent, child) = if self.nodes[b].value < self.nodes[a].value { (b, a) } else { (a, b) };
+        self.nodes[child].parent = Some(parent);
+        self.nodes[child].sibling = self.nodes[parent].child;
+        self.nodes[parent].child = Some(child);
+        self.nodes[parent].order += 1;
+        parent
+    }
+
+    fn add_tree(&mut self, root: usize) {
+        let mut root: usize = root;
+        let mut order: usize = self.nodes[root].order;
+        loop {
+            if order >= self.trees.len() {
+                self.trees.resize(order + 1, None);
+            }
+            match self.trees[order].take() {
+                None => {
+                    self.trees[order] = Some(root);
+                    return;
+                }
+                Some(other) => {
+                    // carry
+                    root = self.link(root, other);
+                    order += 1;
+                }
+            }
+        }
+    }
+
+    fn min_order(&self) -> Option<usize> {
+        // the order of the tree with the smallest root
+        let mut min: Option<usize> = None;
+        for (order, root) in self.trees.iter().enumerate() {
+            if let Some(root) = root {
+                if min.is_none() || self.nodes[*root].value < self.nodes[self.trees[min.unwrap()].unwrap()].value {
+                    min = Some(order);
+                }
+            }
+        }
+        min
+    }
+
+    fn swap_with_parent(&mut self, index: usize, parent: usize) {
+        // swaps the values (and keys) of two nodes, so that the keys follow their values
+        let (node, parent_node) = self.nodes.get2_mut(index, parent);
+        std::mem::swap(&mut node.value, &mut parent_node.value);
+        std::mem::swap(&mut node.key, &mut parent_node.key);
+        let key: usize = self.nodes[index].key;
+        self.keys[key] = index;
+        let key: usize = self.nodes[parent].key;
+        self.keys[key] = parent;
+    }
+}
+
+impl<T: PartialOrd> PriorityQueue<T> for BinomialHeap<T> {
+    type Handle = Handle;
+
+    fn push(&mut self, value: T) -> Handle {
+        let index: usize = self.nodes.insert(Node {
+            value,
+            key: 0,
+            parent: None,
+            child: None,
+            sibling: None,
+            order: 0,
+        });
+        let key: usize = self.keys.insert(index);
+        self.nodes[index].key = key;
+        self.add_tree(index);
+        self.keys.handle(key)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        let root: usize = self.trees[self.min_order()?].unwrap();
+        Some(&self.nodes[root].value)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        let order: usize = self.min_order()?;
+        let root: usize = self.trees[order].take().unwrap();
+        while let Some(None) = self.trees.last() {
+            self.trees.pop();
+        }
+
+        // the children of the root are trees of orders 0 to order - 1
+        let mut child: Option<usize> = self.nodes[root].child;
+        while let Some(c) = child {
+            child = self.nodes[c].sibling;
+            self.nodes[c].parent = None;
+            self.nodes[c].sibling = None;
+            self.add_tree(c);
+        }
+
+        let node: Node<T> = self.nodes.remove(root);
+        self.keys.remove(node.key);
+        Some(node.value)
+    }
+
+    fn get(&self, handle: Handle) -> Option<&T> {
+        let key: usize = self.keys.resolve(handle)?;
+        Some(&self.nodes[self.keys[key]].value)
+    }
+
+    fn decrease_key(&mut self, handle: Handle, value: T) -> Result<(), errordsa::Error> {
+        let key: usize = self.keys.resolve(handle).ok_or(errordsa::Error::InvalidHandle)?;
+        let mut index: usize = self.keys[key];
+        if value > self.nodes[index].value {
+            return Err(errordsa::Error::KeyIncreased);
+        }
+        self.nodes[index].value = value;
+        while let Some(parent) = self.nodes[index].parent {
+            if self.nodes[parent].value <= self.nodes[index].value {
+                break;
+            }
+            self.swap_with_parent(index, parent);
+            index = parent;
+        }
+        Ok(())
+    }
+
+    fn meld(&mut self, other: BinomialHeap<T>) {
+        // the keys of other point to its nodes, which will be shifted by the amount of nodes in this heap
+        let node_offset: usize = self.nodes.slot_count();
+        let key_offset: usize = self.keys.append(other.keys, |node, _| {
+            *node += node_offset;
+        });
+        self.nodes.append(other.nodes, |node, offset| {
+            node.key += key_offset;
+            shift(&mut node.parent, offset);
+            shift(&mut node.child, offset);
+            shift(&mut node.sibling, offset);
+        });
+        for root in other.trees.into_iter().flatten() {
+            self.add_tree(root + node_offset);
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+impl<T: PartialOrd> Default for BinomialHeap<T> {
+    fn default() -> BinomialHeap<T> {
+        BinomialHeap::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trees_like_a_binary_number() {
+        let mut heap: BinomialHeap<i32> = BinomialHeap::new();
+        let mut handles: Vec<Handle> = vec![];
+        for value in [50, 40, 30, 20, 10, 60] {
+            handles.push(heap.push(value));
+        }
+
+        // 6 = 0b110, so there are trees of orders 1 and 2
+        let orders: Vec<bool> = heap.trees
+            .iter()
+            .map(|root| root.is_some())
+            .collect();
+        assert_eq!(vec![false, true, true], orders);
+
+        // 50 is at the bottom of the tree of order 2
+        heap.decrease_key(handles[0], 1).unwrap();
+        assert_eq!(Some(&1), heap.get(handles[0]));
+        assert_eq!(Some(&40), heap.get(handles[1]));
+
+        let mut popped: Vec<i32> = vec![];
+        while let Some(value) = heap.pop() {
+            popped.push(value);
+        }
+        assert_eq!(vec![1, 10, 20, 30, 40, 60], popped);
+    }
+}
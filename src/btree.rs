@@ -0,0 +1,602 @@
+#![allow(dead_code)]
+
+use std::mem;
+use crate::orderedset::OrderedSet;
+
+// A B-tree of order M keeps up to M - 1 sorted values in every node, and a node with k values has k + 1 children
+// (none in the leaves). Nodes are much wider than in a binary tree, so the tree is shallow, and a search reads a few
+// contiguous vecs instead of following a pointer per comparison.
+// All the leaves are at the same depth. An insertion goes into a leaf, and a node that ends up with M values is
+// split in two around its middle value, which moves up to the parent; when the root splits, the tree grows a level.
+// A deletion always removes a value from a leaf (a value in an inner node is swapped with its predecessor first). A
+// node left with less than ceil(M / 2) - 1 values borrows one from a sibling through the parent or, if the siblings
+// have none to spare, is merged with one of them and the value between both in the parent; when the root runs out of
+// values, the tree loses a level.
+
+struct Node<T> {
+    keys: Vec<T>,
+    children: Vec<Node<T>>, // empty in the leaves, one more than keys otherwise
+}
+
+enum Insertion<T> {
+    Duplicate,
+    Done,
+    Split(T, Node<T>), // the middle value and the new node on its right, to be added to the parent
+}
+
+impl<T: Ord> Node<T> {
+    fn new() -> Node<T> {
+        Node {
+            keys: vec![],
+            children: vec![],
+        }
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    fn insert(&mut self, value: T, max_keys: usize) -> Insertion<T> {
+        let i: usize = match self.keys.binary_search(&value) {
+            Ok(_) => return Insertion::Duplicate,
+            Err(i) => i,
+        };
+        if self.is_leaf() {
+            self.keys.insert(i, value);
+        } else {
+            match self.children[i].insert(value, max_keys) {
+                Insertion::Split(middle, right) => {
+                    self.keys.insert(i, middle);
+                    self.children.insert(i + 1, right);
+                }
+                other => return other,
+            }
+        }
+        if self.keys.len() > max_keys {
+            let (middle, right) = self.split();
+            return Insertion::Split(middle, right);
+        }
+        Insertion::Done
+    }
+
+    fn split(&mut self) -> (T, Node<T>) {
+        // the left half stays in this node
+        let middle: usize = self.keys.len() / 2;
+        let right: Node<T> = Node {
+            keys: self.keys.split_off(middle + 1),
+            children: if self.is_leaf() { vec![] } else { self.children.split_off(middle + 1) },
+        };
+        (self.keys.pop().unwrap(), right)
+    }
+
+    fn remove(&mut self, value: &T, min_keys: usize) -> Option<T> {
+        match self.keys.binary_search(value) {
+            Ok(i) => {
+                if self.is_leaf() {
+                    return Some(self.keys.remove(i));
+                }
+                // the predecessor is the biggest value of the left subtree, which is in a leaf
+                let predecessor: T = self.children[i].remove_max(min_keys);
+                let removed: T = mem::replace(&mut self.keys[i], predecessor);
+                self.fix_child(i, min_keys);
+                Some(removed)
+            }
+            Err(i) => {
+                if self.is_leaf() {
+                    return None;
+                }
+                let removed: Option<T> = self.children[i].remove(value, min_keys);
+                if removed.is_some() {
+                    self.fix_child(i, min_keys);
+                }
+                removed
+            }
+        }
+    }
+
+    fn remove_max(&mut self, min_keys: usize) -> T {
+        if self.is_leaf() {
+            return self.keys.pop().unwrap();
+        }
+        let last: usize = self.children.len() - 1;
+        let max: T = self.children[last].remove_max(min_keys);
+        self.fix_child(last, min_keys);
+        max
+    }
+
+    fn fix_child(&mut self, i: usize, min_keys: usize) {
+        // the child i may have one value less than the minimum after a removal
+        if self.children[i].keys.len() >= min_keys {
+            return;
+        }
+        if i > 0 && self.children[i - 1].keys.len() > min_keys {
+            // borrow from the left sibling: its biggest value goes up, and the one in the parent comes down
+            let (before, after) = self.children.split_at_mut(i);
+            let (sibling, child) = (&mut before[i - 1], &mut after[0]);
+            let separator: T = mem::replace(&mut self.keys[i - 1], sibling.keys.pop().unwrap());
+            child.keys.insert(0, separator);
+            if let Some(grandchild) = sibling.children.pop() {
+                child.children.insert(0, grandchild);
+            }
+        } else if i + 1 < self.children.len() && self.children[i + 1].keys.len() > min_keys {
+            // borrow from the right sibling
+            let (before, after) = self.children.split_at_mut(i + 1);
+            let (child, sibling) = (&mut before[i], &mut after[0]);
+            let separator: T = mem::replace(&mut self.keys[i], sibling.keys.remove(0));
+            child.keys.push(separator);
+            if !sibling.is_leaf() {
+                child.children.push(sibling.children.remove(0));
+            }
+        } else {
+            // merge with a sibling, the left one if there's any
+            let left: usize = if i > 0 { i - 1 } else { i };
+            let right: Node<T> = self.children.remove(left + 1);
+            let separator: T = self.keys.remove(left);
+            let node: &mut Node<T> = &mut self.children[left];
+            node.keys.push(separator);
+            node.keys.extend(right.keys);
+            node.children.extend(right.children);
+        }
+    }
+
+    fn first_where<F: Fn(&T) -> bool>(&self, is_after: F) -> Option<&T> {
+        // the first value for which is_after is true, when it's false for all the values before it and true after
+        let mut found: Option<&T> = None;
+        let mut node: &Node<T> = self;
+        loop {
+            let i: usize = node.keys.partition_point(|key| !is_after(key));
+            if i < node.keys.len() {
+                found = Some(&node.keys[i]);
+            }
+            if node.is_leaf() {
+                return found;
+            }
+            node = &node.children[i];
+        }
+    }
+
+    fn last_where<F: Fn(&T) -> bool>(&self, is_before: F) -> Option<&T> {
+        // the last value for which is_before is true, when it's true for all the values before it and false after
+        let mut found: Option<&T> = None;
+        let mut node: &Node<T> = self;
+        loop {
+            let i: usize = node.keys.partition_point(&is_before);
+            if i > 0 {
+                found = Some(&node.keys[i - 1]);
+            }
+            if node.is_leaf() {
+                return found;
+            }
+            node = &node.children[i];
+        }
+    }
+
+    fn check(&self, min: Option<&T>, max: Option<&T>, is_root: bool, max_keys: usize, min_keys: usize) -> Result<usize, String> {
+        // checks the subtree and returns its height. All its values must be between min and max
+        if self.keys.len() > max_keys || (!is_root && self.keys.len() < min_keys) {
+            return Err("a node has a wrong amount of values".to_owned());
+        }
+        if !self.keys.windows(2).all(|pair| pair[0] < pair[1]) {
+            return Err("the values of a node are out of order".to_owned());
+        }
+        if let (Some(first), Some(last)) = (self.keys.first(), self.keys.last()) {
+            if min.is_some_and(|min| first <= min) || max.is_some_and(|max| last >= max) {
+                return Err("the values are out of order".to_owned());
+            }
+        }
+        if self.is_leaf() {
+            return Ok(1);
+        }
+        if self.children.len() != self.keys.len() + 1 {
+            return Err("a node has a wrong amount of children".to_owned());
+        }
+        let mut height: Option<usize> = None;
+        for (i, child) in self.children.iter().enumerate() {
+            let low: Option<&T> = if i == 0 { min } else { Some(&self.keys[i - 1]) };
+            let high: Option<&T> = self.keys.get(i).or(max);
+            let child_height: usize = child.check(low, high, false, max_keys, min_keys)?;
+            if height.is_some_and(|h| h != child_height) {
+                return Err("the leaves are at different depths".to_owned());
+            }
+            height = Some(child_height);
+        }
+        Ok(1 + height.unwrap())
+    }
+}
+
+pub struct BTree<T, const M: usize> {
+    root: Node<T>, // an empty leaf when the tree is empty
+    len: usize,
+}
+
+impl<T: Ord, const M: usize> BTree<T, M> {
+    const MAX_KEYS: usize = M - 1;
+    const MIN_KEYS: usize = M.div_ceil(2) - 1; // except in the root
+
+    pub fn new() -> BTree<T, M> {
+        const { assert!(M >= 3, "a B-tree needs at least 3 children per node") };
+        BTree {
+            root: Node::new(),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn height(&self) -> usize {
+        // 0 for an empty tree
+        if self.is_empty() {
+            return 0;
+        }
+        let mut height: usize = 1;
+        let mut node: &Node<T> = &self.root;
+        while let Some(child) = node.children.first() {
+            node = child;
+            height += 1;
+        }
+        height
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        let mut node: &Node<T> = &self.root;
+        loop {
+            match node.keys.binary_search(value) {
+                Ok(_) => return true,
+                Err(_) if node.is_leaf() => return false,
+                Err(i) => node = &node.children[i],
+            }
+        }
+    }
+
+    pub fn insert(&mut self, value: T) -> bool {
+        // false if the value was already in the tree
+        match self.root.insert(value, Self::MAX_KEYS) {
+            Insertion::Duplicate => return false,
+            Insertion::Done => {}
+            Insertion::Split(middle, right) => {
+                let left: Node<T> = mem::replace(&mut self.root, Node::new());
+                self.root.keys.push(middle);
+                self.root.children.push(left);
+                self.root.children.push(right);
+            }
+        }
+        self.len += 1;
+        true
+    }
+
+    pub fn remove(&mut self, value: &T) -> Option<T> {
+        let removed: Option<T> = self.root.remove(value, Self::MIN_KEYS);
+        if removed.is_some() {
+            self.len -= 1;
+            if self.root.keys.is_empty() && !self.root.is_leaf() {
+                self.root = self.root.children.pop().unwrap();
+            }
+        }
+        removed
+    }
+
+    pub fn min(&self) -> Option<&T> {
+        let mut node: &Node<T> = &self.root;
+        while let Some(child) = node.children.first() {
+            node = child;
+        }
+        node.keys.first()
+    }
+
+    pub fn max(&self) -> Option<&T> {
+        let mut node: &Node<T> = &self.root;
+        while let Some(child) = node.children.last() {
+            node = child;
+        }
+        node.keys.last()
+    }
+
+    pub fn floor(&self, value: &T) -> Option<&T> {
+        self.root.last_where(|key| key <= value)
+    }
+
+    pub fn ceiling(&self, value: &T) -> Option<&T> {
+        self.root.first_where(|key| key >= value)
+    }
+
+    pub fn predecessor(&self, value: &T) -> Option<&T> {
+        self.root.last_where(|key| key < value)
+    }
+
+    pub fn successor(&self, value: &T) -> Option<&T> {
+        self.root.first_where(|key| key > value)
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        let mut iter: Iter<'_, T> = Iter { stack: vec![], remaining: self.len };
+        iter.push_left_path(&self.root);
+        iter
+    }
+
+    pub fn check_invariants(&self) -> Result<(), String> {
+        // the values are ordered, every node has between the minimum and the maximum amount of values and the right
+        // amount of children, and all the leaves are at the same depth
+        self.root.check(None, None, true, Self::MAX_KEYS, Self::MIN_KEYS)?;
+        if self.iter().count() != self.len {
+            return Err("the length is wrong".to_owned());
+        }
+        Ok(())
+    }
+}
+
+impl<T: Ord, const M: usize> Default for BTree<T, M> {
+    fn default() -> BTree<T, M> {
+        BTree::new()
+    }
+}
+
+impl<T: Ord, const M: usize> OrderedSet<T> for BTree<T, M> {
+    fn insert(&mut self, value: T) -> bool {
+        BTree::insert(self, value)
+    }
+
+    fn remove(&mut self, value: &T) -> bool {
+        BTree::remove(self, value).is_some()
+    }
+
+    fn contains(&mut self, value: &T) -> bool {
+        BTree::contains(self, value)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn min(&mut self) -> Option<&T> {
+        BTree::min(self)
+    }
+
+    fn max(&mut self) -> Option<&T> {
+        BTree::max(self)
+    }
+
+    fn floor(&mut self, value: &T) -> Option<&T> {
+        BTree::floor(self, value)
+    }
+
+    fn ceiling(&mut self, value: &T) -> Option<&T> {
+        BTree::ceiling(self, value)
+    }
+
+    fn predecessor(&mut self, value: &T) -> Option<&T> {
+        BTree::predecessor(self, value)
+    }
+
+    fn successor(&mut self, value: &T) -> Option<&T> {
+        BTree::successor(self, value)
+    }
+
+    fn in_order_traverse<F: FnMut(&T)>(&self, visit_func: &mut F) {
+        self.iter().for_each(visit_func);
+    }
+}
+
+// In-order iterator. The stack holds the nodes on the path to the next value, each with the index of its next value
+
+pub struct Iter<'a, T> {
+    stack: Vec<(&'a Node<T>, usize)>,
+    remaining: usize,
+}
+
+impl<'a, T> Iter<'a, T> {
+    fn push_left_path(&mut self, mut node: &'a Node<T>) {
+        loop {
+            self.stack.push((node, 0));
+            match node.children.first() {
+                None => return,
+                Some(child) => node = child,
+            }
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            let (node, i) = self.stack.last_mut()?;
+            let node: &'a Node<T> = node;
+            if *i == node.keys.len() {
+                self.stack.pop();
+                continue;
+            }
+            let value: &'a T = &node.keys[*i];
+            *i += 1;
+            // the values of the child after this one come before the next one of the node
+            if let Some(child) = node.children.get(*i) {
+                self.push_left_path(child);
+            }
+            self.remaining -= 1;
+            return Some(value);
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<'a, T: Ord, const M: usize> IntoIterator for &'a BTree<T, M> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+    use crate::{ binarytree::BinaryTree, testutil::next_random };
+
+    #[test]
+    fn btree_splits_and_merges() {
+        let mut tree: BTree<i32, 3> = BTree::new();
+        assert_eq!(0, tree.height());
+        assert_eq!(None, tree.min());
+
+        // a 2-3 tree: every node has 1 or 2 values
+        for value in 1..=7 {
+            assert!(tree.insert(value));
+            tree.check_invariants().unwrap();
+        }
+        assert!(!tree.insert(4));
+        assert_eq!(3, tree.height());
+        assert_eq!(vec![4], tree.root.keys);
+        assert_eq!(vec![1, 2, 3, 4, 5, 6, 7], tree.iter().copied().collect::<Vec<i32>>());
+
+        // removing a value from an inner node, then merges all the way up
+        assert_eq!(Some(4), tree.remove(&4));
+        tree.check_invariants().unwrap();
+        assert_eq!(None, tree.remove(&4));
+        for value in [1, 2, 3] {
+            tree.remove(&value);
+            tree.check_invariants().unwrap();
+        }
+        assert_eq!(2, tree.height());
+        assert_eq!(vec![5, 6, 7], (&tree).into_iter().copied().collect::<Vec<i32>>());
+        assert_eq!(Some(&5), tree.min());
+        assert_eq!(Some(&7), tree.max());
+
+        for value in [5, 6, 7] {
+            tree.remove(&value);
+        }
+        tree.check_invariants().unwrap();
+        assert!(tree.is_empty());
+        assert_eq!(0, tree.height());
+    }
+
+    fn check_against_btreeset<const M: usize>(seed: u64) {
+        let mut seed: u64 = seed;
+        let mut tree: BTree<u64, M> = BTree::new();
+        let mut expected: BTreeSet<u64> = BTreeSet::new();
+        for _ in 0..5000 {
+            let value: u64 = next_random(&mut seed) % 500;
+            match next_random(&mut seed) % 3 {
+                0 => assert_eq!(expected.take(&value), tree.remove(&value)),
+                1 => assert_eq!(expected.insert(value), tree.insert(value)),
+                _ => assert_eq!(expected.contains(&value), tree.contains(&value)),
+            }
+            tree.check_invariants().unwrap();
+        }
+        assert_eq!(expected.len(), tree.len());
+        assert_eq!(expected.len(), tree.iter().len());
+        assert!(expected.iter().eq(tree.iter()));
+    }
+
+    #[test]
+    fn btree_against_btreeset() {
+        check_against_btreeset::<3>(1);
+        check_against_btreeset::<4>(2);
+        check_against_btreeset::<5>(3);
+        check_against_btreeset::<16>(4);
+    }
+
+    #[test]
+    fn btree_sorted_insertions() {
+        let mut tree: BTree<i32, 32> = BTree::new();
+        for value in 0..100_000 {
+            tree.insert(value);
+        }
+        tree.check_invariants().unwrap();
+        // every node but the last one of each level is left half full
+        assert!(tree.height() <= 5);
+        for value in (0..100_000).step_by(2) {
+            tree.remove(&value);
+        }
+        tree.check_invariants().unwrap();
+        assert_eq!(50_000, tree.len());
+        assert_eq!(Some(&99_999), tree.floor(&100_000));
+        assert_eq!(Some(&1001), tree.ceiling(&1000));
+        assert_eq!(Some(&997), tree.predecessor(&999));
+        assert_eq!(Some(&1001), tree.successor(&999));
+    }
+
+    fn bench_btree<const M: usize>(values: &[u64]) {
+        use std::time::Instant;
+
+        let start: Instant = Instant::now();
+        let mut tree: BTree<u64, M> = BTree::new();
+        for value in values.iter() {
+            tree.insert(*value);
+        }
+        let inserted: Instant = Instant::now();
+        let found: usize = values.iter().filter(|value| tree.contains(value)).count();
+        let searched: Instant = Instant::now();
+        let sum: u64 = tree.iter().sum();
+        let iterated: Instant = Instant::now();
+        for value in values.iter() {
+            tree.remove(value);
+        }
+        let removed: Instant = Instant::now();
+        std::hint::black_box((found, sum));
+
+        println!(
+            "B-tree M = {}: insert {:?}, search {:?}, iterate {:?}, remove {:?}",
+            M,
+            inserted - start,
+            searched - inserted,
+            iterated - searched,
+            removed - iterated
+        );
+    }
+
+    fn bench_binary_tree(values: &[u64]) {
+        use std::time::Instant;
+
+        let start: Instant = Instant::now();
+        let mut tree: BinaryTree<u64> = BinaryTree::new();
+        for value in values.iter() {
+            tree.insert_ordered(*value);
+        }
+        let inserted: Instant = Instant::now();
+        let found: usize = values.iter().filter(|value| tree.depth_first_search(**value)).count();
+        let searched: Instant = Instant::now();
+        let sum: u64 = tree.iter().sum();
+        let iterated: Instant = Instant::now();
+        for value in values.iter() {
+            tree.delete(*value);
+        }
+        let removed: Instant = Instant::now();
+        std::hint::black_box((found, sum));
+
+        println!(
+            "BinaryTree: insert {:?}, search {:?}, iterate {:?}, remove {:?}",
+            inserted - start,
+            searched - inserted,
+            iterated - searched,
+            removed - iterated
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_against_binary_tree() {
+        // cargo test --release btree::tests::bench_against_binary_tree -- --ignored --nocapture
+        // random values, as sorted ones would turn the BinaryTree into a list
+        let mut seed: u64 = 5;
+        let values: Vec<u64> = (0..1_000_000).map(|_| next_random(&mut seed)).collect();
+        bench_binary_tree(&values);
+        bench_btree::<4>(&values);
+        bench_btree::<8>(&values);
+        bench_btree::<16>(&values);
+        bench_btree::<64>(&values);
+        bench_btree::<256>(&values);
+    }
+}
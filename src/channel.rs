@@ -0,0 +1,294 @@
+#![allow(dead_code)]
+
+use std::{ sync::{ Arc, Condvar, Mutex, MutexGuard }, time::Duration };
+
+use crate::ringbuffer::{ Mode, RingBuffer };
+
+// Multi-producer multi-consumer bounded channel. The queue is a fixed-capacity RingBuffer behind a Mutex,
+// and the two Condvars let blocked threads sleep until there's room to send or something to receive.
+// The channel keeps count of the senders and receivers still alive: once all the receivers are gone sending
+// fails, and once all the senders are gone receiving fails as soon as the queue is empty.
+
+#[derive(PartialEq, Debug)]
+pub struct SendError<T>(pub T); // all receivers were dropped, the value is handed back
+
+#[derive(PartialEq, Debug)]
+pub enum TrySendError<T> {
+    Full(T),
+    Disconnected(T),
+}
+
+#[derive(PartialEq, Debug)]
+pub struct RecvError; // all senders were dropped and the channel is empty
+
+#[derive(PartialEq, Debug)]
+pub enum TryRecvError {
+    Empty,
+    Disconnected,
+}
+
+#[derive(PartialEq, Debug)]
+pub enum RecvTimeoutError {
+    Timeout,
+    Disconnected,
+}
+
+struct State<T> {
+    buffer: RingBuffer<T>,
+    senders: usize,
+    receivers: usize,
+}
+
+impl<T> State<T> {
+    fn is_full(&self) -> bool {
+        self.buffer.len() == self.buffer.capacity()
+    }
+}
+
+struct Shared<T> {
+    state: Mutex<State<T>>,
+    not_empty: Condvar,
+    not_full: Condvar,
+}
+
+impl<T> Shared<T> {
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        self.state.lock().unwrap()
+    }
+}
+
+pub struct Sender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+pub struct Receiver<T> {
+    shared: Arc<Shared<T>>,
+}
+
+pub fn bounded<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(capacity > 0, "a channel needs room for at least one value");
+    let shared: Arc<Shared<T>> = Arc::new(Shared {
+        state: Mutex::new(State {
+            buffer: RingBuffer::with_mode(capacity, Mode::Fixed),
+            senders: 1,
+            receivers: 1,
+        }),
+        not_empty: Condvar::new(),
+        not_full: Condvar::new(),
+    });
+    (Sender { shared: shared.clone() }, Receiver { shared })
+}
+
+impl<T> Sender<T> {
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        // blocks while the channel is full
+        let mut state: MutexGuard<'_, State<T>> = self.shared.not_full
+            .wait_while(self.shared.lock(), |s| s.is_full() && s.receivers > 0)
+            .unwrap();
+        if state.receivers == 0 {
+            return Err(SendError(value));
+        }
+        state.buffer.push_back(value).ok(); // there's room, so this can't fail
+        self.shared.not_empty.notify_one();
+        Ok(())
+    }
+
+    pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        let mut state: MutexGuard<'_, State<T>> = self.shared.lock();
+        if state.receivers == 0 {
+            return Err(TrySendError::Disconnected(value));
+        }
+        if state.is_full() {
+            return Err(TrySendError::Full(value));
+        }
+        state.buffer.push_back(value).ok(); // there's room, so this can't fail
+        self.shared.not_empty.notify_one();
+        Ok(())
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Sender<T> {
+        self.shared.lock().senders += 1;
+        Sender { shared: self.shared.clone() }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state: MutexGuard<'_, State<T>> = self.shared.lock();
+        state.senders -= 1;
+        if state.senders == 0 {
+            // wakes up the blocked receivers so they notice there's nothing else coming
+            self.shared.not_empty.notify_all();
+        }
+    }
+}
+
+impl<T> Receiver<T> {
+    pub fn recv(&self) -> Result<T, RecvError> {
+        // blocks while the channel is empty
+        let state: MutexGuard<'_, State<T>> = self.shared.not_empty
+            .wait_while(self.shared.lock(), |s| s.buffer.is_empty() && s.senders > 0)
+            .unwrap();
+        self.take(state).ok_or(RecvError)
+    }
+
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let state: MutexGuard<'_, State<T>> = self.shared.lock();
+        if state.buffer.is_empty() && state.senders == 0 {
+            return Err(TryRecvError::Disconnected);
+        }
+        self.take(state).ok_or(TryRecvError::Empty)
+    }
+
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        let (state, _) = self.shared.not_empty
+            .wait_timeout_while(self.shared.lock(), timeout, |s| s.buffer.is_empty() && s.senders > 0)
+            .unwrap();
+        if state.buffer.is_empty() && state.senders == 0 {
+            return Err(RecvTimeoutError::Disconnected);
+        }
+        self.take(state).ok_or(RecvTimeoutError::Timeout)
+    }
+
+    fn take(&self, mut state: MutexGuard<'_, State<T>>) -> Option<T> {
+        let value: Option<T> = state.buffer.pop_front();
+        if value.is_some() {
+            self.shared.not_full.notify_one();
+        }
+        value
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Receiver<T> {
+        self.shared.lock().receivers += 1;
+        Receiver { shared: self.shared.clone() }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let mut state: MutexGuard<'_, State<T>> = self.shared.lock();
+        state.receivers -= 1;
+        if state.receivers == 0 {
+            // wakes up the blocked senders so they can get their values back
+            self.shared.not_full.notify_all();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn send_and_recv() {
+        let (tx, rx) = bounded::<i32>(2);
+
+        tx.send(0).unwrap();
+        tx.try_send(1).unwrap();
+        assert_eq!(Err(TrySendError::Full(2)), tx.try_send(2));
+
+        assert_eq!(Ok(0), rx.recv());
+        tx.send(2).unwrap();
+        assert_eq!(Ok(1), rx.try_recv());
+        assert_eq!(Ok(2), rx.recv_timeout(Duration::from_millis(10)));
+        assert_eq!(Err(TryRecvError::Empty), rx.try_recv());
+        assert_eq!(Err(RecvTimeoutError::Timeout), rx.recv_timeout(Duration::from_millis(10)));
+    }
+
+    #[test]
+    fn disconnect_senders() {
+        let (tx, rx) = bounded::<i32>(2);
+        let tx2: Sender<i32> = tx.clone();
+
+        tx.send(0).unwrap();
+        drop(tx);
+        tx2.send(1).unwrap();
+        drop(tx2);
+
+        // what was already sent can still be received
+        assert_eq!(Ok(0), rx.recv());
+        assert_eq!(Ok(1), rx.try_recv());
+        assert_eq!(Err(RecvError), rx.recv());
+        assert_eq!(Err(TryRecvError::Disconnected), rx.try_recv());
+        assert_eq!(Err(RecvTimeoutError::Disconnected), rx.recv_timeout(Duration::from_millis(10)));
+    }
+
+    #[test]
+    fn disconnect_receivers() {
+        let (tx, rx) = bounded::<String>(1);
+        let rx2: Receiver<String> = rx.clone();
+
+        drop(rx);
+        tx.send("a".to_owned()).unwrap();
+        drop(rx2);
+        assert_eq!(Err(SendError("b".to_owned())), tx.send("b".to_owned()));
+        assert_eq!(Err(TrySendError::Disconnected("c".to_owned())), tx.try_send("c".to_owned()));
+    }
+
+    #[test]
+    fn wakes_up_blocked_threads() {
+        let (tx, rx) = bounded::<i32>(1);
+        tx.send(0).unwrap();
+
+        // the sender blocks until the receiver is dropped
+        let handle = thread::spawn(move || tx.send(1));
+        thread::sleep(Duration::from_millis(20));
+        drop(rx);
+        assert_eq!(Err(SendError(1)), handle.join().unwrap());
+
+        // the receiver blocks until the sender is dropped
+        let (tx, rx) = bounded::<i32>(1);
+        let handle = thread::spawn(move || rx.recv());
+        thread::sleep(Duration::from_millis(20));
+        drop(tx);
+        assert_eq!(Err(RecvError), handle.join().unwrap());
+    }
+
+    #[test]
+    fn many_producers_and_consumers() {
+        let (tx, rx) = bounded::<u64>(4);
+        let mut producers: Vec<thread::JoinHandle<()>> = vec![];
+        let mut consumers: Vec<thread::JoinHandle<u64>> = vec![];
+
+        for p in 0..4 {
+            let tx: Sender<u64> = tx.clone();
+            producers.push(
+                thread::spawn(move || {
+                    for i in 0..1000 {
+                        tx.send(p * 1000 + i).unwrap();
+                    }
+                })
+            );
+        }
+        drop(tx);
+
+        for _ in 0..3 {
+            let rx: Receiver<u64> = rx.clone();
+            consumers.push(
+                thread::spawn(move || {
+                    let mut sum: u64 = 0;
+                    while let Ok(value) = rx.recv() {
+                        sum += value;
+                    }
+                    sum
+                })
+            );
+        }
+        drop(rx);
+
+        for handle in producers {
+            handle.join().unwrap();
+        }
+        let total: u64 = consumers
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .sum();
+        assert_eq!((0..4000).sum::<u64>(), total);
+    }
+}
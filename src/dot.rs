@@ -0,0 +1,128 @@
+#![allow(dead_code)]
+
+// Helpers for the to_dot exporters of binarytree::BinaryTree, tree::Tree and graph::Graph, which write the structure
+// as Graphviz DOT text to look at it while debugging (`dot -Tsvg tree.dot -o tree.svg`). The nodes are named n0, n1...
+// and labeled with their values, and the nodes and edges of the highlighted path are drawn in red.
+// Both trees are written by tree, which only needs to know how to get the value and the children of a node.
+
+use std::fmt::Display;
+
+pub fn tree<'a, 'p, T, N, V, C, I>(root: Option<&'a N>, value: V, children: C, path: &'p [T]) -> String
+where
+    T: PartialEq + Display + 'a,
+    V: Fn(&'a N) -> &'a T,
+    C: Fn(&'a N) -> I,
+    I: Iterator<Item = (Option<&'static str>, &'a N)>,
+{
+    // children gives the label of the edge to every child too. path is a walk down from the root: its first value
+    // must be the one of the root, the next one the value of one of its children, and so on. Those nodes and the
+    // edges between them are highlighted
+    let mut result: String = String::new();
+    begin(&mut result);
+    // the nodes still to write, in pre-order, with the edge from their parent and the rest of the walk if they are
+    // on it
+    let mut stack: Vec<Pending<'a, 'p, N, T>> = vec![];
+    if let Some(root) = root {
+        let walk: Option<&[T]> = match path {
+            [first, rest @ ..] if value(root) == first => Some(rest),
+            _ => None,
+        };
+        stack.push(Pending { node: root, parent: None, walk });
+    }
+    let mut next_id: usize = 0;
+    while let Some(Pending { node: current, parent, walk }) = stack.pop() {
+        let id: usize = next_id;
+        next_id += 1;
+        if let Some((parent, label)) = parent {
+            edge(&mut result, parent, id, label, walk.is_some());
+        }
+        node(&mut result, id, &value(current).to_string(), walk.is_some());
+
+        // the first child with the next value continues the walk
+        let mut rest: Option<&[T]> = walk;
+        let mut pending: Vec<Pending<'a, 'p, N, T>> = children(current)
+            .map(|(label, child)| {
+                let walk: Option<&[T]> = match rest {
+                    Some([next, tail @ ..]) if value(child) == next => {
+                        rest = None;
+                        Some(tail)
+                    }
+                    _ => None,
+                };
+                Pending { node: child, parent: Some((id, label)), walk }
+            })
+            .collect();
+        pending.reverse();
+        stack.extend(pending);
+    }
+    end(&mut result);
+    result
+}
+
+struct Pending<'a, 'p, N, T> {
+    node: &'a N,
+    parent: Option<(usize, Option<&'static str>)>,
+    walk: Option<&'p [T]>,
+}
+
+pub fn begin(dot: &mut String) {
+    dot.push_str("digraph {\n");
+}
+
+pub fn end(dot: &mut String) {
+    dot.push_str("}\n");
+}
+
+pub fn node(dot: &mut String, id: usize, label: &str, highlighted: bool) {
+    let mut attributes: Vec<String> = vec![format!("label=\"{}\"", escape(label))];
+    if highlighted {
+        attributes.push("color=red".to_owned());
+    }
+    dot.push_str(&format!("    n{} [{}];\n", id, attributes.join(", ")));
+}
+
+pub fn edge(dot: &mut String, from: usize, to: usize, label: Option<&str>, highlighted: bool) {
+    let mut attributes: Vec<String> = vec![];
+    if let Some(label) = label {
+        attributes.push(format!("label=\"{}\"", escape(label)));
+    }
+    if highlighted {
+        attributes.push("color=red".to_owned());
+        attributes.push("penwidth=2".to_owned());
+    }
+    if attributes.is_empty() {
+        dot.push_str(&format!("    n{} -> n{};\n", from, to));
+    } else {
+        dot.push_str(&format!("    n{} -> n{} [{}];\n", from, to, attributes.join(", ")));
+    }
+}
+
+fn escape(label: &str) -> String {
+    // inside quotes, only quotes and backslashes need escaping
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dot_lines() {
+        let mut dot: String = String::new();
+        begin(&mut dot);
+        node(&mut dot, 0, "say \"hi\"", false);
+        node(&mut dot, 1, "C:\\", true);
+        edge(&mut dot, 0, 1, None, false);
+        edge(&mut dot, 1, 0, Some("-3"), true);
+        end(&mut dot);
+        let expected: &str = "\
+digraph {
+    n0 [label=\"say \\\"hi\\\"\"];
+    n1 [label=\"C:\\\\\", color=red];
+    n0 -> n1;
+    n1 -> n0 [label=\"-3\", color=red, penwidth=2];
+}
+";
+        assert_eq!(expected, dot);
+    }
+}
@@ -1,4 +1,10 @@
 #[derive(PartialEq, Debug)]
 pub enum Error {
     IndexOutOfBound,
+    BufferFull,
+    InvalidHandle,
+    KeyIncreased,
+    TraversalLengthMismatch,
+    InconsistentTraversals,
+    InvalidFormat,
 }
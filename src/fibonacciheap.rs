@@ -0,0 +1,289 @@
+#![allow(dead_code)]
+
+use crate::{ errordsa, mergeableheap::{ shift, Arena, Handle, PriorityQueue } };
+
+// A Fibonacci heap is a list of trees (the root list) plus a pointer to the smallest root. It's lazy: pushing and
+// melding just add trees to the root list, and the work of tidying it up is left for pop, which links roots of the
+// same degree until there's at most one of each, like in a binomial heap.
+// Decreasing a key cuts the node from its parent when it becomes smaller than it, and makes it a root. To keep the
+// trees bushy, a node that loses a second child is cut too (that's what `marked` is for), which may cascade up.
+// All the sibling lists are circular and doubly linked, so nodes can be removed and lists joined in O(1).
+// With all this, pop is amortized O(log n), and push, meld and decrease_key are amortized O(1).
+
+struct Node<T> {
+    value: T,
+    parent: Option<usize>,
+    child: Option<usize>, // any of the children
+    left: usize,
+    right: usize,
+    degree: usize, // amount of children
+    marked: bool, // it lost a child since it became a child itself
+}
+
+pub struct FibonacciHeap<T> {
+    nodes: Arena<Node<T>>,
+    min: Option<usize>,
+}
+
+impl<T: PartialOrd> FibonacciHeap<T> {
+    pub fn new() -> FibonacciHeap<T> {
+        FibonacciHeap {
+            nodes: Arena::new(),
+            min: None,
+        }
+    }
+
+    fn splice(&mut self, a: usize, b: usize) {
+        // joins the circular lists of a and b into one
+        let a_right: usize = self.nodes[a].right;
+        let b_left: usize = self.nodes[b].left;
+        self.nodes[a].right = b;
+        self.nodes[b].left = a;
+        self.nodes[b_left].right = a_right;
+        self.nodes[a_right].left = b_left;
+    }
+
+    fn unlink(&mut self, index: usize) {
+        // removes a node from its list, leaving it in a list of its own
+        let left: usize = self.nodes[index].left;
+        let right: usize = self.nodes[index].right;
+        self.nodes[left].right = right;
+        self.nodes[right].left = left;
+        self.nodes[index].left = index;
+        self.nodes[index].right = index;
+    }
+
+    fn siblings(&self, index: usize) -> Vec<usize> {
+        // all the nodes in the list of a node, starting by itself
+        let mut list: Vec<usize> = vec![index];
+        let mut current: usize = self.nodes[index].right;
+        while current != index {
+            list.push(current);
+            current = self.nodes[current].right;
+        }
+        list
+    }
+
+    fn add_root(&mut self, index: usize) {
+        match self.min {
+            None => {
+                self.min = Some(index);
+            }
+            Some(min) => {
+                self.splice(min, index);
+                if self.nodes[index].value < self.nodes[min].value {
+                    self.min = Some(index);
+                }
+            }
+        }
+    }
+
+    fn link(&mut self, child: usize, parent: usize) {
+        // both are roots, and child becomes a child of parent
+        self.unlink(child);
+        match self.nodes[parent].child {
+            None => {
+                self.nodes[parent].child = Some(child);
+            }
+            Some(first) => self.splice(first, child),
+        }
+        self.nodes[child].parent = Some(parent);
+        self.nodes[child].marked = false;
+        self.nodes[parent].degree += 1;
+    }
+
+    fn consolidate(&mut self) {
+        // links roots of the same degree until all of them have a different one
+        let mut by_degree: Vec<Option<usize>> = vec![];
+        for root in self.siblings(self.min.unwrap()) {
+            let mut root: usize = root;
+            let mut degree: usize = self.nodes[root].degree;
+            loop {
+                if degree >= by_degree.len() {
+                    by_degree.resize(degree + 1, None);
+                }
+                match by_degree[degree].take() {
+                    None => {
+                        by_degree[degree] = Some(root);
+                        break;
+                    }
+                    Some(mut other) => {
+                        if self.nodes[other].value < self.nodes[root].value {
+                            std::mem::swap(&mut root, &mut other);
+                        }
+                        self.link(other, root);
+                        degree += 1;
+                    }
+                }
+            }
+        }
+
+        self.min = None;
+        for root in by_degree.into_iter().flatten() {
+            if self.min.is_none() || self.nodes[root].value < self.nodes[self.min.unwrap()].value {
+                self.min = Some(root);
+            }
+        }
+    }
+
+    fn cut(&mut self, index: usize, parent: usize) {
+        // moves a node from the children of parent to the root list
+        if self.nodes[parent].child == Some(index) {
+            let right: usize = self.nodes[index].right;
+            self.nodes[parent].child = if right == index { None } else { Some(right) };
+        }
+        self.unlink(index);
+        self.nodes[parent].degree -= 1;
+        self.nodes[index].parent = None;
+        self.nodes[index].marked = false;
+        self.add_root(index);
+    }
+
+    fn cascading_cut(&mut self, index: usize) {
+        let mut index: usize = index;
+        while let Some(parent) = self.nodes[index].parent {
+            if !self.nodes[index].marked {
+                self.nodes[index].marked = true;
+                return;
+            }
+            self.cut(index, parent);
+            index = parent;
+        }
+    }
+}
+
+impl<T: PartialOrd> PriorityQueue<T> for FibonacciHeap<T> {
+    type Handle = Handle;
+
+    fn push(&mut self, value: T) -> Handle {
+        let index: usize = self.nodes.insert(Node {
+            value,
+            parent: None,
+            child: None,
+            left: 0,
+            right: 0,
+            degree: 0,
+            marked: false,
+        });
+        self.nodes[index].left = index;
+        self.nodes[index].right = index;
+        self.add_root(index);
+        self.nodes.handle(index)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        Some(&self.nodes[self.min?].value)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        let min: usize = self.min?;
+
+        // the children become roots
+        if let Some(child) = self.nodes[min].child.take() {
+            for c in self.siblings(child) {
+                self.nodes[c].parent = None;
+            }
+            self.splice(min, child);
+        }
+
+        let next: usize = self.nodes[min].right;
+        self.unlink(min);
+        if next == min {
+            self.min = None;
+        } else {
+            self.min = Some(next);
+            self.consolidate();
+        }
+        Some(self.nodes.remove(min).value)
+    }
+
+    fn get(&self, handle: Handle) -> Option<&T> {
+        Some(&self.nodes[self.nodes.resolve(handle)?].value)
+    }
+
+    fn decrease_key(&mut self, handle: Handle, value: T) -> Result<(), errordsa::Error> {
+        let index: usize = self.nodes.resolve(handle).ok_or(errordsa::Error::InvalidHandle)?;
+        if value > self.nodes[index].value {
+            return Err(errordsa::Error::KeyIncreased);
+        }
+        self.nodes[index].value = value;
+        if let Some(parent) = self.nodes[index].parent {
+            if self.nodes[index].value < self.nodes[parent].value {
+                self.cut(index, parent);
+                self.cascading_cut(parent);
+            }
+        }
+        if self.nodes[index].value < self.nodes[self.min.unwrap()].value {
+            self.min = Some(index);
+        }
+        Ok(())
+    }
+
+    fn meld(&mut self, other: FibonacciHeap<T>) {
+        let offset: usize = self.nodes.append(other.nodes, |node, offset| {
+            shift(&mut node.parent, offset);
+            shift(&mut node.child, offset);
+            node.left += offset;
+            node.right += offset;
+        });
+        if let Some(other_min) = other.min {
+            self.add_root(other_min + offset);
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+impl<T: PartialOrd> Default for FibonacciHeap<T> {
+    fn default() -> FibonacciHeap<T> {
+        FibonacciHeap::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cascading_cuts() {
+        let mut heap: FibonacciHeap<i32> = FibonacciHeap::new();
+        for value in 0..9 {
+            heap.push(value * 10);
+        }
+
+        // consolidating 8 nodes builds a single tree of degree 3
+        assert_eq!(Some(0), heap.pop());
+        assert_eq!(1, heap.siblings(heap.min.unwrap()).len());
+
+        // cutting two children of the same node cuts that node too
+        let root: usize = heap.min.unwrap();
+        let node: usize = heap
+            .siblings(heap.nodes[root].child.unwrap())
+            .into_iter()
+            .find(|child| heap.nodes[*child].degree == 2)
+            .unwrap();
+        let children: Vec<Handle> = heap
+            .siblings(heap.nodes[node].child.unwrap())
+            .into_iter()
+            .map(|child| heap.nodes.handle(child))
+            .collect();
+        heap.decrease_key(children[0], 5).unwrap();
+        assert!(heap.nodes[node].marked);
+        assert_eq!(Some(root), heap.nodes[node].parent);
+        heap.decrease_key(children[1], 1).unwrap();
+        assert_eq!(None, heap.nodes[node].parent);
+        assert!(!heap.nodes[node].marked);
+        assert_eq!(Some(&1), heap.peek());
+        assert_eq!(4, heap.siblings(root).len());
+
+        let mut popped: Vec<i32> = vec![];
+        while let Some(value) = heap.pop() {
+            popped.push(value);
+        }
+        assert_eq!(8, popped.len());
+        assert_eq!([1, 5, 10], popped[..3]);
+        assert!(popped.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+}
@@ -2,7 +2,7 @@
 
 use std::collections::VecDeque;
 
-use crate::heap::MinHeap;
+use crate::{ dot, indexedheap::{ Identified, IndexedMinHeap }, mergeableheap::PriorityQueue };
 
 struct DijkstraTableItem {
     dist: u64, // all weights must be non-negative (of course, this is not a good way of checking that)
@@ -10,25 +10,16 @@ struct DijkstraTableItem {
     previous: Option<usize>,
 }
 
-#[derive(PartialEq, Eq, Copy, Clone)]
-struct DijkstraHeapItem {
+// what dijkstra_shortest_path_with keeps in its queue, ordered by distance
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+pub struct DijkstraHeapItem {
+    dist: u64,
     index: usize,
-    dist: u64, // shortest distance from source
 }
 
-impl Ord for DijkstraHeapItem {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        if self.dist < other.dist {
-            return std::cmp::Ordering::Less;
-        } else {
-            return std::cmp::Ordering::Greater;
-        }
-    }
-}
-
-impl PartialOrd for DijkstraHeapItem {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
+impl Identified for DijkstraHeapItem {
+    fn id(&self) -> usize {
+        self.index
     }
 }
 
@@ -152,12 +143,27 @@ impl Graph {
         dest: usize,
         path: &mut Vec<usize>,
         cost: &mut u64
+    ) {
+        // The queue is indexed by vertex, so when a shorter distance is found the vertex's priority is
+        // decreased instead of pushing it again
+        self.dijkstra_shortest_path_with::<IndexedMinHeap<DijkstraHeapItem>>(source, dest, path, cost);
+    }
+
+    pub fn dijkstra_shortest_path_with<Q: PriorityQueue<DijkstraHeapItem> + Default>(
+        &self,
+        source: usize,
+        dest: usize,
+        path: &mut Vec<usize>,
+        cost: &mut u64
     ) {
         // https://www.youtube.com/watch?v=EFg3u_E6eHU
         // https://doc.rust-lang.org/std/collections/binary_heap/index.html
+        // Any PriorityQueue works as the queue, like the mergeable heaps. The handle of every vertex in the queue
+        // is kept, so its distance can be decreased instead of pushing it again
 
         let mut table: Vec<DijkstraTableItem> = vec![];
-        let mut queue: MinHeap<DijkstraHeapItem> = MinHeap::<DijkstraHeapItem>::new();
+        let mut handles: Vec<Option<Q::Handle>> = vec![None; self.vertex_amount()];
+        let mut queue: Q = Q::default();
 
         for i in 0..self.vertex_amount() {
             table.push(DijkstraTableItem {
@@ -171,11 +177,9 @@ impl Graph {
             });
         }
 
-        queue.insert(DijkstraHeapItem { index: source, dist: 0 });
-
-        while queue.len() > 0 {
-            let current: usize = queue.pop().unwrap().index;
+        handles[source] = Some(queue.push(DijkstraHeapItem { dist: 0, index: source }));
 
+        while let Some(DijkstraHeapItem { index: current, .. }) = queue.pop() {
             table[current].seen = true;
 
             if current == dest {
@@ -183,17 +187,25 @@ impl Graph {
             }
 
             for e in self.adj_list[current].iter() {
-                if table[current].dist + (e.weight as u64) < table[e.to].dist {
-                    table[e.to].dist = table[current].dist + (e.weight as u64);
+                let dist: u64 = table[current].dist + (e.weight as u64);
+                if !table[e.to].seen && dist < table[e.to].dist {
+                    table[e.to].dist = dist;
                     table[e.to].previous = Some(current);
-                }
-
-                if !table[e.to].seen {
-                    queue.insert(DijkstraHeapItem { index: e.to, dist: table[e.to].dist });
+                    let item: DijkstraHeapItem = DijkstraHeapItem { dist, index: e.to };
+                    match handles[e.to] {
+                        Some(handle) => queue.decrease_key(handle, item).unwrap(),
+                        None => {
+                            handles[e.to] = Some(queue.push(item));
+                        }
+                    }
                 }
             }
         }
 
+        Graph::dijkstra_path(&table, dest, path, cost);
+    }
+
+    fn dijkstra_path(table: &[DijkstraTableItem], dest: usize, path: &mut Vec<usize>, cost: &mut u64) {
         if !table[dest].seen {
             *cost = u64::MAX;
             return;
@@ -208,11 +220,30 @@ impl Graph {
         }
         path.reverse();
     }
+
+    pub fn to_dot(&self, path: &[usize]) -> String {
+        // every vertex, with its index as its label, and every edge with its weight. The vertices of path and the
+        // edges between consecutive ones are highlighted, so it can show the result of bfs_path or dijkstra
+        let mut result: String = String::new();
+        dot::begin(&mut result);
+        for v in 0..self.vertex_amount() {
+            dot::node(&mut result, v, &v.to_string(), path.contains(&v));
+        }
+        for (v, edges) in self.adj_list.iter().enumerate() {
+            for e in edges.iter() {
+                let highlighted: bool = path.windows(2).any(|pair| pair == [v, e.to]);
+                dot::edge(&mut result, v, e.to, Some(&e.weight.to_string()), highlighted);
+            }
+        }
+        dot::end(&mut result);
+        result
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{ binomialheap::BinomialHeap, fibonacciheap::FibonacciHeap, pairingheap::PairingHeap };
 
     #[test]
     fn bfs_path_search() {
@@ -280,8 +311,7 @@ mod tests {
         assert_eq!(Vec::<usize>::new(), path3);
     }
 
-    #[test]
-    fn shortest_path() {
+    fn shortest_path_graph() -> Graph {
         let mut graph: Graph = Graph::new();
 
         graph.add_vertex();
@@ -303,6 +333,12 @@ mod tests {
         graph.add_edge(1, 5, 10);
         graph.add_edge(1, 0, 10);
         graph.add_edge(5, 3, 1);
+        graph
+    }
+
+    #[test]
+    fn shortest_path() {
+        let graph: Graph = shortest_path_graph();
 
         let mut path1: Vec<usize> = vec![];
         let mut path2: Vec<usize> = vec![];
@@ -322,4 +358,60 @@ mod tests {
         assert_eq!(3, cost2);
         assert_eq!(u64::MAX, cost3);
     }
+
+    fn check_shortest_path_with<Q: PriorityQueue<DijkstraHeapItem> + Default>() {
+        let graph: Graph = shortest_path_graph();
+
+        for (source, dest, expected_path, expected_cost) in [
+            (0, 5, vec![0, 2, 4, 5], 3),
+            (2, 1, vec![2, 4, 3, 1], 3),
+            (1, 6, vec![], u64::MAX),
+        ] {
+            let mut path: Vec<usize> = vec![];
+            let mut cost: u64 = 0;
+            graph.dijkstra_shortest_path_with::<Q>(source, dest, &mut path, &mut cost);
+            assert_eq!(expected_path, path);
+            assert_eq!(expected_cost, cost);
+        }
+    }
+
+    #[test]
+    fn shortest_path_with_mergeable_heaps() {
+        check_shortest_path_with::<PairingHeap<DijkstraHeapItem>>();
+        check_shortest_path_with::<BinomialHeap<DijkstraHeapItem>>();
+        check_shortest_path_with::<FibonacciHeap<DijkstraHeapItem>>();
+        check_shortest_path_with::<IndexedMinHeap<DijkstraHeapItem>>();
+    }
+
+    #[test]
+    fn graph_to_dot() {
+        let mut graph: Graph = Graph::new();
+        for _ in 0..4 {
+            graph.add_vertex();
+        }
+        graph.add_edge(0, 1, 4);
+        graph.add_edge(0, 2, 1);
+        graph.add_edge(2, 1, 2);
+        graph.add_edge(1, 3, -5);
+
+        let mut path: Vec<usize> = vec![];
+        let mut cost: u64 = u64::MAX;
+        graph.dijkstra_shortest_path(0, 1, &mut path, &mut cost);
+        assert_eq!(vec![0, 2, 1], path);
+
+        let expected: &str = "\
+digraph {
+    n0 [label=\"0\", color=red];
+    n1 [label=\"1\", color=red];
+    n2 [label=\"2\", color=red];
+    n3 [label=\"3\"];
+    n0 -> n1 [label=\"4\"];
+    n0 -> n2 [label=\"1\", color=red, penwidth=2];
+    n1 -> n3 [label=\"-5\"];
+    n2 -> n1 [label=\"2\", color=red, penwidth=2];
+}
+";
+        assert_eq!(expected, graph.to_dot(&path));
+        assert!(!graph.to_dot(&[]).contains("red"));
+    }
 }
@@ -1,140 +1,183 @@
 #![allow(dead_code)]
 
-pub struct MaxHeap<T: PartialOrd> {
-    // also called Priority Queue
-    arr: Vec<T>,
+use std::{ cmp::Ordering, ops::{ Deref, DerefMut }, slice, vec };
+
+// MaxHeap and MinHeap only differ in the direction of the comparisons, so both are the same BinaryHeap with a
+// different Compare. Any other order can be used through a closure: `by` takes a comparator like the one in
+// `sort_by`, and `by_key` a function that extracts the key to compare. In both cases the biggest element is the
+// one at the top, like in MaxHeap (use std::cmp::Reverse, or swap the arguments, to get the smallest one).
+// The heap is a D-ary tree stored in a vec: the children of index i are D * i + 1 to D * i + D. BinaryHeap is the
+// usual D = 2. With more children per node the tree is shallower, so going up (insert, decreasing a key in a
+// MinHeap) is cheaper, while going down (pop) compares more children per level
+
+pub trait Compare<T> {
+    // true if `a` must be closer to the top of the heap than `b`
+    fn higher(&self, a: &T, b: &T) -> bool;
 }
 
-impl<T: PartialOrd + Copy> MaxHeap<T> {
-    pub fn new() -> MaxHeap<T> {
-        MaxHeap { arr: Vec::<T>::new() }
-    }
+#[derive(Default, Clone, Copy)]
+pub struct Max;
 
-    pub fn len(&self) -> usize {
-        self.arr.len()
-    }
+#[derive(Default, Clone, Copy)]
+pub struct Min;
 
-    pub fn insert(&mut self, value: T) {
-        self.arr.push(value);
-        self.heapify_up(self.len() - 1);
-    }
+pub struct By<F>(F);
 
-    pub fn pop(&mut self) -> Option<T> {
-        if self.len() == 0 {
-            return None;
-        }
-        let r: T = self.arr[0];
-        self.arr[0] = self.arr[self.len() - 1];
-        self.arr.remove(self.len() - 1);
-        self.heapify_down(0);
-        return Some(r);
-    }
+pub struct ByKey<F>(F);
 
-    pub fn edit<F: Fn(&T) -> bool>(&mut self, f: F, new: T) {
-        let mut idx: Option<usize> = None;
-        let mut go_up: bool = false;
-        for (i, e) in self.arr.iter_mut().enumerate() {
-            if f(e) {
-                if new > *e {
-                    go_up = true;
-                }
-                *e = new;
-                idx = Some(i);
-                break;
-            }
-        }
-        if idx.is_some() {
-            if go_up {
-                self.heapify_up(idx.unwrap());
-            } else {
-                self.heapify_down(idx.unwrap());
-            }
-        }
+impl<T: PartialOrd> Compare<T> for Max {
+    fn higher(&self, a: &T, b: &T) -> bool {
+        a > b
     }
+}
 
-    fn parent(index: usize) -> usize {
-        return (index - 1) / 2;
+impl<T: PartialOrd> Compare<T> for Min {
+    fn higher(&self, a: &T, b: &T) -> bool {
+        a < b
     }
+}
 
-    fn left_child(index: usize) -> usize {
-        return index * 2 + 1;
+impl<T, F: Fn(&T, &T) -> Ordering> Compare<T> for By<F> {
+    fn higher(&self, a: &T, b: &T) -> bool {
+        (self.0)(a, b) == Ordering::Greater
     }
+}
 
-    fn right_child(index: usize) -> usize {
-        return index * 2 + 2;
+impl<T, K: PartialOrd, F: Fn(&T) -> K> Compare<T> for ByKey<F> {
+    fn higher(&self, a: &T, b: &T) -> bool {
+        (self.0)(a) > (self.0)(b)
     }
+}
 
-    fn heapify_up(&mut self, index: usize) {
-        if index == 0 {
-            return;
-        }
+pub struct DaryHeap<T, C: Compare<T>, const D: usize> {
+    // also called Priority Queue
+    arr: Vec<T>,
+    compare: C,
+}
 
-        let parent_idx: usize = MaxHeap::<T>::parent(index);
+pub type BinaryHeap<T, C> = DaryHeap<T, C, 2>;
 
-        if self.arr[index] > self.arr[parent_idx] {
-            let swap_aux: T = self.arr[index];
-            self.arr[index] = self.arr[parent_idx];
-            self.arr[parent_idx] = swap_aux;
-            self.heapify_up(parent_idx);
-        }
+pub type MaxHeap<T> = BinaryHeap<T, Max>;
+
+pub type MinHeap<T> = BinaryHeap<T, Min>;
+
+pub type DaryMaxHeap<T, const D: usize> = DaryHeap<T, Max, D>;
+
+pub type DaryMinHeap<T, const D: usize> = DaryHeap<T, Min, D>;
+
+impl<T, C: Compare<T> + Default, const D: usize> DaryHeap<T, C, D> {
+    pub fn new() -> DaryHeap<T, C, D> {
+        DaryHeap::with_comparator(C::default())
     }
 
-    fn heapify_down(&mut self, index: usize) {
-        let left_idx: usize = MaxHeap::<T>::left_child(index);
-        let right_idx: usize = MaxHeap::<T>::right_child(index);
+    pub fn from_vec(vec: Vec<T>) -> DaryHeap<T, C, D> {
+        DaryHeap::from_vec_with_comparator(vec, C::default())
+    }
+}
 
-        if index >= self.len() || left_idx >= self.len() {
-            return;
-        }
-        if
-            (right_idx >= self.len() || self.arr[left_idx] > self.arr[right_idx]) &&
-            self.arr[left_idx] > self.arr[index]
-        {
-            let swap_aux: T = self.arr[index];
-            self.arr[index] = self.arr[left_idx];
-            self.arr[left_idx] = swap_aux;
-            self.heapify_down(left_idx);
-        } else if
-            right_idx < self.len() &&
-            self.arr[left_idx] < self.arr[right_idx] &&
-            self.arr[right_idx] > self.arr[index]
-        {
-            let swap_aux: T = self.arr[index];
-            self.arr[index] = self.arr[right_idx];
-            self.arr[right_idx] = swap_aux;
-            self.heapify_down(right_idx);
-        }
+impl<T, F: Fn(&T, &T) -> Ordering, const D: usize> DaryHeap<T, By<F>, D> {
+    pub fn by(f: F) -> DaryHeap<T, By<F>, D> {
+        DaryHeap::with_comparator(By(f))
     }
 }
 
-pub struct MinHeap<T: PartialOrd> {
-    // also called Priority Queue
-    arr: Vec<T>,
+impl<T, K: PartialOrd, F: Fn(&T) -> K, const D: usize> DaryHeap<T, ByKey<F>, D> {
+    pub fn by_key(f: F) -> DaryHeap<T, ByKey<F>, D> {
+        DaryHeap::with_comparator(ByKey(f))
+    }
 }
 
-impl<T: PartialOrd + Copy> MinHeap<T> {
-    pub fn new() -> MinHeap<T> {
-        MinHeap { arr: Vec::<T>::new() }
+impl<T, C: Compare<T>, const D: usize> DaryHeap<T, C, D> {
+    pub fn with_comparator(compare: C) -> DaryHeap<T, C, D> {
+        const { assert!(D >= 2, "a heap needs at least 2 children per node") };
+        DaryHeap { arr: Vec::<T>::new(), compare }
+    }
+
+    pub fn from_vec_with_comparator(vec: Vec<T>, compare: C) -> DaryHeap<T, C, D> {
+        let mut heap: DaryHeap<T, C, D> = DaryHeap::with_comparator(compare);
+        heap.arr = vec;
+        heap.rebuild();
+        heap
     }
 
     pub fn len(&self) -> usize {
         self.arr.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.arr.is_empty()
+    }
+
     pub fn insert(&mut self, value: T) {
         self.arr.push(value);
         self.heapify_up(self.len() - 1);
     }
 
     pub fn pop(&mut self) -> Option<T> {
-        if self.len() == 0 {
+        if self.is_empty() {
             return None;
         }
-        let r: T = self.arr[0];
-        self.arr[0] = self.arr[self.len() - 1];
-        self.arr.remove(self.len() - 1);
+        // the last element takes the place of the top one, so the top can be removed from the end of the vec
+        let last: usize = self.len() - 1;
+        self.arr.swap(0, last);
+        let r: Option<T> = self.arr.pop();
         self.heapify_down(0);
-        return Some(r);
+        r
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.arr.first()
+    }
+
+    pub fn peek_mut(&mut self) -> Option<PeekMut<'_, T, C, D>> {
+        // the top can be modified through the guard, and it's moved to its new place when the guard is dropped
+        if self.is_empty() {
+            return None;
+        }
+        Some(PeekMut { heap: self })
+    }
+
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        // in no particular order
+        self.arr.iter()
+    }
+
+    pub fn drain(&mut self) -> vec::Drain<'_, T> {
+        // in no particular order
+        self.arr.drain(..)
+    }
+
+    pub fn clear(&mut self) {
+        self.arr.clear();
+    }
+
+    pub fn append(&mut self, other: &mut DaryHeap<T, C, D>) {
+        // moves all the elements of other, leaving it empty
+        let old_len: usize = self.len();
+        self.arr.append(&mut other.arr);
+        if self.len() - old_len < old_len {
+            for i in old_len..self.len() {
+                self.heapify_up(i);
+            }
+        } else {
+            // when there are many new elements, building the heap again is cheaper than inserting them one by one
+            self.rebuild();
+        }
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        // in no particular order
+        self.arr
+    }
+
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        // heapsort: the top is swapped with the last element of the heap, which then gets one element shorter.
+        // So the vec ends up sorted from the lowest element to the top one (ascending for MaxHeap, descending for MinHeap)
+        for end in (1..self.len()).rev() {
+            self.arr.swap(0, end);
+            self.heapify_down_until(0, end);
+        }
+        self.arr
     }
 
     pub fn edit<F: Fn(&T) -> bool>(&mut self, f: F, new: T) {
@@ -142,7 +185,7 @@ impl<T: PartialOrd + Copy> MinHeap<T> {
         let mut go_up: bool = false;
         for (i, e) in self.arr.iter_mut().enumerate() {
             if f(e) {
-                if new < *e {
+                if self.compare.higher(&new, e) {
                     go_up = true;
                 }
                 *e = new;
@@ -150,25 +193,21 @@ impl<T: PartialOrd + Copy> MinHeap<T> {
                 break;
             }
         }
-        if idx.is_some() {
+        if let Some(idx) = idx {
             if go_up {
-                self.heapify_up(idx.unwrap());
+                self.heapify_up(idx);
             } else {
-                self.heapify_down(idx.unwrap());
+                self.heapify_down(idx);
             }
         }
     }
 
     fn parent(index: usize) -> usize {
-        return (index - 1) / 2;
+        (index - 1) / D
     }
 
-    fn left_child(index: usize) -> usize {
-        return index * 2 + 1;
-    }
-
-    fn right_child(index: usize) -> usize {
-        return index * 2 + 2;
+    fn first_child(index: usize) -> usize {
+        index * D + 1
     }
 
     fn heapify_up(&mut self, index: usize) {
@@ -176,47 +215,93 @@ impl<T: PartialOrd + Copy> MinHeap<T> {
             return;
         }
 
-        let parent_idx: usize = MinHeap::<T>::parent(index);
+        let parent_idx: usize = Self::parent(index);
 
-        if self.arr[index] < self.arr[parent_idx] {
-            let swap_aux: T = self.arr[index];
-            self.arr[index] = self.arr[parent_idx];
-            self.arr[parent_idx] = swap_aux;
+        if self.compare.higher(&self.arr[index], &self.arr[parent_idx]) {
+            self.arr.swap(index, parent_idx);
             self.heapify_up(parent_idx);
         }
     }
 
     fn heapify_down(&mut self, index: usize) {
-        let left_idx: usize = MinHeap::<T>::left_child(index);
-        let right_idx: usize = MinHeap::<T>::right_child(index);
+        self.heapify_down_until(index, self.len());
+    }
 
-        if index >= self.len() || left_idx >= self.len() {
+    fn heapify_down_until(&mut self, index: usize, end: usize) {
+        // only the first `end` elements are part of the heap
+        let first_idx: usize = Self::first_child(index);
+
+        if first_idx >= end {
             return;
         }
-        if
-            (right_idx >= self.len() || self.arr[left_idx] < self.arr[right_idx]) &&
-            self.arr[left_idx] < self.arr[index]
-        {
-            let swap_aux: T = self.arr[index];
-            self.arr[index] = self.arr[left_idx];
-            self.arr[left_idx] = swap_aux;
-            self.heapify_down(left_idx);
-        } else if
-            right_idx < self.len() &&
-            self.arr[left_idx] > self.arr[right_idx] &&
-            self.arr[right_idx] < self.arr[index]
-        {
-            let swap_aux: T = self.arr[index];
-            self.arr[index] = self.arr[right_idx];
-            self.arr[right_idx] = swap_aux;
-            self.heapify_down(right_idx);
+
+        // the child that goes up is the highest one, so it ends up above its siblings
+        let mut child_idx: usize = first_idx;
+        for i in first_idx + 1..end.min(first_idx + D) {
+            if self.compare.higher(&self.arr[i], &self.arr[child_idx]) {
+                child_idx = i;
+            }
         }
+
+        if self.compare.higher(&self.arr[child_idx], &self.arr[index]) {
+            self.arr.swap(index, child_idx);
+            self.heapify_down_until(child_idx, end);
+        }
+    }
+
+    fn rebuild(&mut self) {
+        // bottom-up heapify: every parent is pushed down, starting from the last one. It's O(n) because most of
+        // the elements are near the bottom, where heapify_down has little to do
+        if self.len() < 2 {
+            return;
+        }
+        for i in (0..=Self::parent(self.len() - 1)).rev() {
+            self.heapify_down(i);
+        }
+    }
+}
+
+impl<T, C: Compare<T> + Default, const D: usize> From<Vec<T>> for DaryHeap<T, C, D> {
+    fn from(vec: Vec<T>) -> DaryHeap<T, C, D> {
+        DaryHeap::from_vec(vec)
+    }
+}
+
+pub struct PeekMut<'a, T, C: Compare<T>, const D: usize = 2> {
+    heap: &'a mut DaryHeap<T, C, D>,
+}
+
+impl<T, C: Compare<T>, const D: usize> PeekMut<'_, T, C, D> {
+    pub fn pop(this: PeekMut<'_, T, C, D>) -> T {
+        // popping goes through DaryHeap::pop, so there's nothing left to fix when the guard is dropped
+        this.heap.pop().unwrap()
+    }
+}
+
+impl<T, C: Compare<T>, const D: usize> Deref for PeekMut<'_, T, C, D> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.heap.arr[0]
+    }
+}
+
+impl<T, C: Compare<T>, const D: usize> DerefMut for PeekMut<'_, T, C, D> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.heap.arr[0]
+    }
+}
+
+impl<T, C: Compare<T>, const D: usize> Drop for PeekMut<'_, T, C, D> {
+    fn drop(&mut self) {
+        self.heap.heapify_down(0);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testutil::next_random;
 
     #[test]
     fn max_heap_insertion() {
@@ -297,4 +382,195 @@ mod tests {
         assert_eq!(Some(912), heap.pop());
         assert_eq!(None, heap.pop());
     }
+
+    #[test]
+    fn heap_by_closure() {
+        let mut heap = BinaryHeap::by(|a: &(i32, char), b: &(i32, char)| b.1.cmp(&a.1));
+        heap.insert((3, 'c'));
+        heap.insert((1, 'a'));
+        heap.insert((2, 'b'));
+
+        assert_eq!(Some((1, 'a')), heap.pop());
+        assert_eq!(Some((2, 'b')), heap.pop());
+        assert_eq!(Some((3, 'c')), heap.pop());
+        assert_eq!(None, heap.pop());
+    }
+
+    #[test]
+    fn heap_by_key() {
+        let mut heap = BinaryHeap::by_key(|value: &i32| value.abs());
+        heap.insert(-3);
+        heap.insert(1);
+        heap.insert(-7);
+        heap.insert(5);
+        heap.edit(|e| *e == 1, 10);
+
+        assert_eq!(Some(10), heap.pop());
+        assert_eq!(Some(-7), heap.pop());
+        assert_eq!(Some(5), heap.pop());
+        assert_eq!(Some(-3), heap.pop());
+        assert_eq!(None, heap.pop());
+    }
+
+    #[test]
+    fn heap_of_strings() {
+        let mut heap: MinHeap<String> = MinHeap::new();
+        heap.insert("pear".to_owned());
+        heap.insert("apple".to_owned());
+        heap.insert("fig".to_owned());
+
+        assert_eq!(Some(&"apple".to_owned()), heap.peek());
+        assert_eq!(Some("apple".to_owned()), heap.pop());
+        assert_eq!(Some("fig".to_owned()), heap.pop());
+        assert_eq!(Some("pear".to_owned()), heap.pop());
+        assert_eq!(None, heap.peek());
+    }
+
+    #[test]
+    fn heap_peek_mut() {
+        let mut heap: MaxHeap<i32> = MaxHeap::from_vec(vec![5, 9, 1, 7]);
+        assert!(MaxHeap::<i32>::new().peek_mut().is_none());
+
+        {
+            let mut top: PeekMut<'_, i32, Max> = heap.peek_mut().unwrap();
+            assert_eq!(9, *top);
+            *top = 2;
+        }
+        assert_eq!(Some(&7), heap.peek());
+
+        let top: PeekMut<'_, i32, Max> = heap.peek_mut().unwrap();
+        assert_eq!(7, PeekMut::pop(top));
+        assert_eq!(vec![1, 2, 5], heap.into_sorted_vec());
+    }
+
+    #[test]
+    fn heap_from_vec() {
+        let values: Vec<i32> = vec![300, 30, 230, 40, 50, 700, 912, 30, 1];
+        let heap: MinHeap<i32> = MinHeap::from_vec(values.clone());
+        assert_eq!(9, heap.len());
+        assert_eq!(Some(&1), heap.peek());
+        assert_eq!(vec![912, 700, 300, 230, 50, 40, 30, 30, 1], heap.into_sorted_vec());
+
+        let heap: MaxHeap<i32> = values.clone().into();
+        let mut sorted: Vec<i32> = values.clone();
+        sorted.sort();
+        assert_eq!(sorted, heap.into_sorted_vec());
+
+        let heap = BinaryHeap::from_vec_with_comparator(vec!["ccc", "a", "bb"], ByKey(|s: &&str| s.len()));
+        assert_eq!(vec!["a", "bb", "ccc"], heap.into_sorted_vec());
+        assert_eq!(Vec::<i32>::new(), MaxHeap::<i32>::from_vec(vec![]).into_sorted_vec());
+    }
+
+    #[test]
+    fn heap_iter_and_drain() {
+        let mut heap: MaxHeap<i32> = MaxHeap::from_vec(vec![3, 1, 2]);
+        let mut values: Vec<i32> = heap.iter().copied().collect();
+        values.sort();
+        assert_eq!(vec![1, 2, 3], values);
+
+        let mut drained: Vec<i32> = heap.drain().collect();
+        drained.sort();
+        assert_eq!(vec![1, 2, 3], drained);
+        assert!(heap.is_empty());
+
+        heap.insert(4);
+        let mut values: Vec<i32> = heap.into_vec();
+        values.sort();
+        assert_eq!(vec![4], values);
+    }
+
+    #[test]
+    fn heap_append() {
+        let mut a: MinHeap<i32> = MinHeap::from_vec(vec![8, 3, 5, 10, 12]);
+        let mut b: MinHeap<i32> = MinHeap::from_vec(vec![4, 1]);
+        let mut c: MinHeap<i32> = MinHeap::from_vec(vec![11, 2, 9, 6, 7, 0]);
+
+        a.append(&mut b); // few elements, inserted one by one
+        a.append(&mut c); // many elements, rebuilds the heap
+        assert!(b.is_empty());
+        assert!(c.is_empty());
+
+        let mut popped: Vec<i32> = vec![];
+        while let Some(value) = a.pop() {
+            popped.push(value);
+        }
+        assert_eq!((0..13).collect::<Vec<i32>>(), popped);
+    }
+
+    fn check_dary_heap<const D: usize>() {
+        let mut seed: u64 = 11;
+        let values: Vec<u64> = (0..200).map(|_| next_random(&mut seed) % 100).collect();
+        let mut sorted: Vec<u64> = values.clone();
+        sorted.sort();
+
+        let mut heap: DaryMinHeap<u64, D> = DaryMinHeap::new();
+        for value in values.iter() {
+            heap.insert(*value);
+        }
+        heap.edit(|e| *e == values[0], 0);
+        let mut expected: Vec<u64> = values.clone();
+        *expected.iter_mut().find(|e| **e == values[0]).unwrap() = 0;
+        expected.sort();
+        let mut popped: Vec<u64> = vec![];
+        while let Some(value) = heap.pop() {
+            popped.push(value);
+        }
+        assert_eq!(expected, popped);
+
+        let heap: DaryMaxHeap<u64, D> = DaryMaxHeap::from_vec(values.clone());
+        assert_eq!(Some(sorted.last().unwrap()), heap.peek());
+        assert_eq!(sorted, heap.into_sorted_vec());
+    }
+
+    #[test]
+    fn dary_heap() {
+        check_dary_heap::<2>();
+        check_dary_heap::<3>();
+        check_dary_heap::<4>();
+        check_dary_heap::<8>();
+
+        let mut heap: DaryMinHeap<i32, 4> = DaryMinHeap::new();
+        for value in [5, 3, 8, 1, 9, 2] {
+            heap.insert(value);
+        }
+        // the children of the root are the next 4 elements, and 2 goes up from being the first child of 5
+        assert_eq!(vec![1, 2, 8, 3, 9, 5], heap.arr);
+    }
+
+    fn bench_dary_heap<const D: usize>(values: &[u64]) {
+        use std::time::Instant;
+
+        let start: Instant = Instant::now();
+        let mut heap: DaryMinHeap<u64, D> = DaryMinHeap::new();
+        for value in values.iter() {
+            heap.insert(*value);
+        }
+        let inserted: Instant = Instant::now();
+        while heap.pop().is_some() {}
+        let popped: Instant = Instant::now();
+        let heap: DaryMinHeap<u64, D> = DaryMinHeap::from_vec(values.to_vec());
+        let built: Instant = Instant::now();
+        std::hint::black_box(heap);
+
+        println!(
+            "D = {}: insert {:?}, pop {:?}, from_vec {:?}",
+            D,
+            inserted - start,
+            popped - inserted,
+            built - popped
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_arity() {
+        // cargo test --release heap::tests::bench_arity -- --ignored --nocapture
+        let mut seed: u64 = 3;
+        let values: Vec<u64> = (0..1_000_000).map(|_| next_random(&mut seed)).collect();
+        bench_dary_heap::<2>(&values);
+        bench_dary_heap::<3>(&values);
+        bench_dary_heap::<4>(&values);
+        bench_dary_heap::<8>(&values);
+        bench_dary_heap::<16>(&values);
+    }
 }
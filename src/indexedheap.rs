@@ -0,0 +1,304 @@
+#![allow(dead_code)]
+
+use crate::{ errordsa, heap::{ Compare, Max, Min }, mergeableheap::PriorityQueue };
+
+// Priority queue where every element is identified by a usize id (for example the index of a vertex), so its
+// priority can be changed or it can be removed without searching for it.
+// Besides the heap of ids, it keeps where each id is in the heap and its priority, indexed by id, which is why
+// ids should be small numbers: the tables are as long as the biggest id pushed.
+// An IndexedMinHeap of elements that carry their own id is also a PriorityQueue, with the ids as handles, so it can
+// be used wherever the mergeable heaps are (like in graph::Graph::dijkstra_shortest_path_with).
+
+pub struct IndexedHeap<P, C: Compare<P>> {
+    heap: Vec<usize>, // ids
+    positions: Vec<Option<usize>>, // index in the heap of every id
+    priorities: Vec<Option<P>>,
+    compare: C,
+}
+
+pub type IndexedMaxHeap<P> = IndexedHeap<P, Max>;
+
+pub type IndexedMinHeap<P> = IndexedHeap<P, Min>;
+
+impl<P, C: Compare<P> + Default> IndexedHeap<P, C> {
+    pub fn new() -> IndexedHeap<P, C> {
+        IndexedHeap::with_comparator(C::default())
+    }
+}
+
+impl<P, C: Compare<P> + Default> Default for IndexedHeap<P, C> {
+    fn default() -> IndexedHeap<P, C> {
+        IndexedHeap::new()
+    }
+}
+
+impl<P, C: Compare<P>> IndexedHeap<P, C> {
+    pub fn with_comparator(compare: C) -> IndexedHeap<P, C> {
+        IndexedHeap {
+            heap: Vec::<usize>::new(),
+            positions: Vec::<Option<usize>>::new(),
+            priorities: Vec::<Option<P>>::new(),
+            compare,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    pub fn contains(&self, id: usize) -> bool {
+        self.position(id).is_some()
+    }
+
+    pub fn priority(&self, id: usize) -> Option<&P> {
+        self.priorities.get(id)?.as_ref()
+    }
+
+    pub fn peek(&self) -> Option<(usize, &P)> {
+        let id: usize = *self.heap.first()?;
+        Some((id, self.priorities[id].as_ref().unwrap()))
+    }
+
+    pub fn push(&mut self, id: usize, priority: P) -> Option<P> {
+        // if the id is already in the heap its priority gets changed, and the old one is returned
+        if self.contains(id) {
+            return self.change_priority(id, priority);
+        }
+        if id >= self.positions.len() {
+            self.positions.resize_with(id + 1, || None);
+            self.priorities.resize_with(id + 1, || None);
+        }
+        self.heap.push(id);
+        self.positions[id] = Some(self.len() - 1);
+        self.priorities[id] = Some(priority);
+        self.heapify_up(self.len() - 1);
+        None
+    }
+
+    pub fn pop(&mut self) -> Option<(usize, P)> {
+        let id: usize = *self.heap.first()?;
+        let priority: P = self.remove(id).unwrap();
+        Some((id, priority))
+    }
+
+    pub fn change_priority(&mut self, id: usize, priority: P) -> Option<P> {
+        // returns the old priority, or None (without pushing anything) if the id isn't in the heap
+        let index: usize = self.position(id)?;
+        let old: Option<P> = self.priorities[id].replace(priority);
+        // only one of them actually moves the element
+        self.heapify_up(index);
+        self.heapify_down(self.positions[id].unwrap());
+        old
+    }
+
+    pub fn remove(&mut self, id: usize) -> Option<P> {
+        let index: usize = self.position(id)?;
+        let last: usize = self.len() - 1;
+        self.swap(index, last);
+        self.heap.pop();
+        self.positions[id] = None;
+        if index < self.len() {
+            // the element that took its place may have to go either way
+            self.heapify_up(index);
+            let moved: usize = self.heap[index];
+            self.heapify_down(self.positions[moved].unwrap());
+        }
+        self.priorities[id].take()
+    }
+
+    fn position(&self, id: usize) -> Option<usize> {
+        *self.positions.get(id)?
+    }
+
+    fn higher(&self, a: usize, b: usize) -> bool {
+        // compares the elements at two indexes of the heap
+        let a: &P = self.priorities[self.heap[a]].as_ref().unwrap();
+        let b: &P = self.priorities[self.heap[b]].as_ref().unwrap();
+        self.compare.higher(a, b)
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        self.heap.swap(a, b);
+        self.positions[self.heap[a]] = Some(a);
+        self.positions[self.heap[b]] = Some(b);
+    }
+
+    fn heapify_up(&mut self, index: usize) {
+        if index == 0 {
+            return;
+        }
+
+        let parent_idx: usize = (index - 1) / 2;
+
+        if self.higher(index, parent_idx) {
+            self.swap(index, parent_idx);
+            self.heapify_up(parent_idx);
+        }
+    }
+
+    fn heapify_down(&mut self, index: usize) {
+        let left_idx: usize = index * 2 + 1;
+        let right_idx: usize = index * 2 + 2;
+
+        if left_idx >= self.len() {
+            return;
+        }
+
+        let mut child_idx: usize = left_idx;
+        if right_idx < self.len() && self.higher(right_idx, left_idx) {
+            child_idx = right_idx;
+        }
+
+        if self.higher(child_idx, index) {
+            self.swap(index, child_idx);
+            self.heapify_down(child_idx);
+        }
+    }
+}
+
+pub trait Identified {
+    fn id(&self) -> usize;
+}
+
+impl<T: PartialOrd + Identified> PriorityQueue<T> for IndexedMinHeap<T> {
+    type Handle = usize;
+
+    fn push(&mut self, value: T) -> usize {
+        // an element with the same id as one in the heap replaces it
+        let id: usize = value.id();
+        IndexedHeap::push(self, id, value);
+        id
+    }
+
+    fn peek(&self) -> Option<&T> {
+        Some(IndexedHeap::peek(self)?.1)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        Some(IndexedHeap::pop(self)?.1)
+    }
+
+    fn get(&self, handle: usize) -> Option<&T> {
+        self.priority(handle)
+    }
+
+    fn decrease_key(&mut self, handle: usize, value: T) -> Result<(), errordsa::Error> {
+        let current: &T = self.priority(handle).ok_or(errordsa::Error::InvalidHandle)?;
+        if value > *current {
+            return Err(errordsa::Error::KeyIncreased);
+        }
+        self.change_priority(handle, value);
+        Ok(())
+    }
+
+    fn meld(&mut self, mut other: IndexedMinHeap<T>) {
+        while let Some((id, value)) = IndexedHeap::pop(&mut other) {
+            IndexedHeap::push(self, id, value);
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.heap.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_and_pop() {
+        let mut heap: IndexedMinHeap<u32> = IndexedMinHeap::new();
+        assert_eq!(None, heap.push(3, 30));
+        assert_eq!(None, heap.push(0, 300));
+        assert_eq!(None, heap.push(7, 70));
+        assert_eq!(Some(70), heap.push(7, 7));
+        assert_eq!(3, heap.len());
+
+        assert!(heap.contains(0));
+        assert!(!heap.contains(1));
+        assert!(!heap.contains(100));
+        assert_eq!(Some(&30), heap.priority(3));
+        assert_eq!(Some((7, &7)), heap.peek());
+
+        assert_eq!(Some((7, 7)), heap.pop());
+        assert_eq!(Some((3, 30)), heap.pop());
+        assert_eq!(Some((0, 300)), heap.pop());
+        assert_eq!(None, heap.pop());
+        assert!(!heap.contains(7));
+    }
+
+    #[test]
+    fn change_priority() {
+        let mut heap: IndexedMaxHeap<i32> = IndexedMaxHeap::new();
+        for id in 0..10 {
+            heap.push(id, (id as i32) * 10);
+        }
+
+        assert_eq!(Some(30), heap.change_priority(3, 1000));
+        assert_eq!(Some(90), heap.change_priority(9, -5));
+        assert_eq!(None, heap.change_priority(42, 1));
+        assert!(!heap.contains(42));
+
+        let mut order: Vec<usize> = vec![];
+        while let Some((id, _)) = heap.pop() {
+            order.push(id);
+        }
+        assert_eq!(vec![3, 8, 7, 6, 5, 4, 2, 1, 0, 9], order);
+    }
+
+    #[test]
+    fn remove() {
+        let mut heap: IndexedMinHeap<i32> = IndexedMinHeap::new();
+        for (id, priority) in [5, 1, 8, 3, 9, 2, 7].into_iter().enumerate() {
+            heap.push(id, priority);
+        }
+
+        assert_eq!(Some(1), heap.remove(1));
+        assert_eq!(Some(9), heap.remove(4));
+        assert_eq!(None, heap.remove(4));
+        assert_eq!(5, heap.len());
+
+        let mut priorities: Vec<i32> = vec![];
+        while let Some((_, priority)) = heap.pop() {
+            priorities.push(priority);
+        }
+        assert_eq!(vec![2, 3, 5, 7, 8], priorities);
+    }
+
+    #[derive(PartialEq, PartialOrd, Debug)]
+    struct Item {
+        priority: u32,
+        id: usize,
+    }
+
+    impl Identified for Item {
+        fn id(&self) -> usize {
+            self.id
+        }
+    }
+
+    #[test]
+    fn as_priority_queue() {
+        let mut heap: IndexedMinHeap<Item> = IndexedMinHeap::default();
+        let a: usize = PriorityQueue::push(&mut heap, Item { priority: 50, id: 0 });
+        let b: usize = PriorityQueue::push(&mut heap, Item { priority: 20, id: 4 });
+        assert_eq!(4, b);
+        assert_eq!(Some(&Item { priority: 20, id: 4 }), PriorityQueue::peek(&heap));
+
+        assert_eq!(Ok(()), heap.decrease_key(a, Item { priority: 10, id: 0 }));
+        assert_eq!(Err(errordsa::Error::KeyIncreased), heap.decrease_key(b, Item { priority: 30, id: 4 }));
+        assert_eq!(Err(errordsa::Error::InvalidHandle), heap.decrease_key(7, Item { priority: 1, id: 7 }));
+
+        let mut other: IndexedMinHeap<Item> = IndexedMinHeap::new();
+        PriorityQueue::push(&mut other, Item { priority: 15, id: 2 });
+        heap.meld(other);
+        assert_eq!(3, PriorityQueue::len(&heap));
+        let order: Vec<usize> = std::iter::from_fn(|| PriorityQueue::pop(&mut heap)).map(|item| item.id).collect();
+        assert_eq!(vec![0, 2, 4], order);
+    }
+}
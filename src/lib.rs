@@ -5,6 +5,27 @@ mod errordsa;
 mod algorithms;
 mod tree;
 mod ringbuffer;
+mod spsc;
+mod channel;
+mod slidingwindow;
 mod binarytree;
+mod avltree;
+mod redblacktree;
+mod orderedset;
+mod treap;
+mod splaytree;
+mod btree;
 mod heap;
+mod minmaxheap;
+mod streaming;
+mod scheduler;
+mod indexedheap;
+mod mergeableheap;
+mod pairingheap;
+mod binomialheap;
+mod fibonacciheap;
 mod graph;
+mod dot;
+
+#[cfg(test)]
+mod testutil;
@@ -0,0 +1,297 @@
+#![allow(dead_code)]
+
+use std::{ ops::{ Index, IndexMut }, sync::atomic::{ AtomicUsize, Ordering } };
+
+use crate::errordsa;
+
+// Common interface of the min-heaps that can be melded (merged) and whose elements can have their key decreased:
+// PairingHeap, BinomialHeap and FibonacciHeap. Pushing returns a Handle to the element, which is what
+// decrease_key takes. Handles stop working once their element is popped, and keep working after their heap is
+// melded into another one.
+
+pub trait PriorityQueue<T> {
+    type Handle: Copy;
+
+    fn push(&mut self, value: T) -> Self::Handle;
+
+    fn peek(&self) -> Option<&T>;
+
+    fn pop(&mut self) -> Option<T>;
+
+    fn get(&self, handle: Self::Handle) -> Option<&T>;
+
+    // the new value can't be bigger than the current one
+    fn decrease_key(&mut self, handle: Self::Handle, value: T) -> Result<(), errordsa::Error>;
+
+    // moves all the elements of other into this heap
+    fn meld(&mut self, other: Self);
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// The heaps keep their nodes in an Arena and link them by index, which avoids having to share ownership of the
+// nodes (a node is reachable from its parent, its siblings and the handles).
+// Freed slots are reused, so every slot has a generation that changes when its node is removed: a handle only
+// works if its generation matches.
+// Melding appends the other arena at the end of this one, so the indexes of the other heap get shifted. Every arena
+// has a unique id, and remembers the ids of the arenas it absorbed and by how much they were shifted, so their handles
+// can still be found.
+
+static NEXT_ARENA_ID: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Handle {
+    arena: usize,
+    index: usize,
+    generation: usize,
+}
+
+struct Slot<N> {
+    node: Option<N>,
+    generation: usize,
+}
+
+pub struct Arena<N> {
+    id: usize,
+    slots: Vec<Slot<N>>,
+    free: Vec<usize>,
+    absorbed: Vec<(usize, usize)>, // (id, offset) of the arenas appended to this one
+    len: usize,
+}
+
+impl<N> Arena<N> {
+    pub fn new() -> Arena<N> {
+        Arena {
+            id: NEXT_ARENA_ID.fetch_add(1, Ordering::Relaxed),
+            slots: Vec::<Slot<N>>::new(),
+            free: Vec::<usize>::new(),
+            absorbed: Vec::<(usize, usize)>::new(),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn slot_count(&self) -> usize {
+        // the offset that the nodes of an arena appended right now would get
+        self.slots.len()
+    }
+
+    pub fn insert(&mut self, node: N) -> usize {
+        self.len += 1;
+        match self.free.pop() {
+            Some(index) => {
+                self.slots[index].node = Some(node);
+                index
+            }
+            None => {
+                self.slots.push(Slot { node: Some(node), generation: 0 });
+                self.slots.len() - 1
+            }
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) -> N {
+        let slot: &mut Slot<N> = &mut self.slots[index];
+        slot.generation += 1; // the handles of the removed node stop working
+        self.free.push(index);
+        self.len -= 1;
+        slot.node.take().unwrap()
+    }
+
+    pub fn handle(&self, index: usize) -> Handle {
+        Handle { arena: self.id, index, generation: self.slots[index].generation }
+    }
+
+    pub fn resolve(&self, handle: Handle) -> Option<usize> {
+        // the index of the node of a handle, if it's still in the arena
+        let mut index: usize = handle.index;
+        if handle.arena != self.id {
+            let (_, offset) = self.absorbed.iter().find(|(id, _)| *id == handle.arena)?;
+            index += offset;
+        }
+        let slot: &Slot<N> = self.slots.get(index)?;
+        if slot.generation != handle.generation || slot.node.is_none() {
+            return None;
+        }
+        Some(index)
+    }
+
+    pub fn get2_mut(&mut self, a: usize, b: usize) -> (&mut N, &mut N) {
+        // two different nodes at once
+        assert_ne!(a, b);
+        if a < b {
+            let (left, right) = self.slots.split_at_mut(b);
+            (left[a].node.as_mut().unwrap(), right[0].node.as_mut().unwrap())
+        } else {
+            let (left, right) = self.slots.split_at_mut(a);
+            (right[0].node.as_mut().unwrap(), left[b].node.as_mut().unwrap())
+        }
+    }
+
+    pub fn append<F: FnMut(&mut N, usize)>(&mut self, other: Arena<N>, mut shift: F) -> usize {
+        // moves the nodes of other to the end of this arena, and returns by how much their indexes were shifted.
+        // `shift` gets every moved node and the offset, and has to shift the indexes the node links to
+        let offset: usize = self.slots.len();
+        self.absorbed.push((other.id, offset));
+        for (id, absorbed_offset) in other.absorbed {
+            self.absorbed.push((id, absorbed_offset + offset));
+        }
+        self.free.extend(other.free.iter().map(|index| index + offset));
+        self.len += other.len;
+        for mut slot in other.slots {
+            if let Some(node) = slot.node.as_mut() {
+                shift(node, offset);
+            }
+            self.slots.push(slot);
+        }
+        offset
+    }
+}
+
+impl<N> Index<usize> for Arena<N> {
+    type Output = N;
+
+    fn index(&self, index: usize) -> &N {
+        self.slots[index].node.as_ref().unwrap()
+    }
+}
+
+impl<N> IndexMut<usize> for Arena<N> {
+    fn index_mut(&mut self, index: usize) -> &mut N {
+        self.slots[index].node.as_mut().unwrap()
+    }
+}
+
+pub fn shift(link: &mut Option<usize>, offset: usize) {
+    if let Some(index) = link.as_mut() {
+        *index += offset;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ binomialheap::BinomialHeap, fibonacciheap::FibonacciHeap, pairingheap::PairingHeap, testutil::next_random };
+
+    fn check_against_sorted_vec<Q: PriorityQueue<u64> + Default>() {
+        let mut queue: Q = Q::default();
+        let mut handles: Vec<(Q::Handle, u64)> = vec![];
+        let mut seed: u64 = 7;
+
+        for _ in 0..300 {
+            match next_random(&mut seed) % 4 {
+                0 | 1 => {
+                    let value: u64 = next_random(&mut seed) % 1000;
+                    handles.push((queue.push(value), value));
+                }
+                2 => {
+                    if handles.is_empty() {
+                        continue;
+                    }
+                    let i: usize = (next_random(&mut seed) as usize) % handles.len();
+                    let (handle, value) = handles[i];
+                    let new: u64 = value - (next_random(&mut seed) % (value + 1));
+                    queue.decrease_key(handle, new).unwrap();
+                    handles[i].1 = new;
+                }
+                _ => {
+                    let expected: Option<u64> = handles
+                        .iter()
+                        .map(|(_, value)| *value)
+                        .min();
+                    assert_eq!(expected.as_ref(), queue.peek());
+                    let popped: Option<u64> = queue.pop();
+                    assert_eq!(expected, popped);
+                    // the handle of the popped element is the only one that stopped working
+                    if let Some(i) = handles.iter().position(|(handle, _)| queue.get(*handle).is_none()) {
+                        let (handle, value) = handles.remove(i);
+                        assert_eq!(Some(value), popped);
+                        assert_eq!(Err(errordsa::Error::InvalidHandle), queue.decrease_key(handle, 0));
+                    }
+                }
+            }
+            assert_eq!(handles.len(), queue.len());
+        }
+
+        let mut expected: Vec<u64> = handles
+            .iter()
+            .map(|(_, value)| *value)
+            .collect();
+        expected.sort();
+        let mut popped: Vec<u64> = vec![];
+        while let Some(value) = queue.pop() {
+            popped.push(value);
+        }
+        assert_eq!(expected, popped);
+        assert!(queue.is_empty());
+    }
+
+    fn check_meld<Q: PriorityQueue<u64> + Default>() {
+        // the slot of a popped element is reused, but its handle doesn't point to the new one
+        let mut a: Q = Q::default();
+        let old: Q::Handle = a.push(1);
+        a.pop();
+        let new: Q::Handle = a.push(2);
+        assert_eq!(None, a.get(old));
+        assert_eq!(Some(&2), a.get(new));
+        a.pop();
+
+        let mut b: Q = Q::default();
+        let mut c: Q = Q::default();
+        let mut handles: Vec<Q::Handle> = vec![];
+
+        for value in [50, 20, 80, 10] {
+            handles.push(a.push(value));
+        }
+        for value in [60, 30, 90] {
+            handles.push(b.push(value));
+        }
+        for value in [70, 40] {
+            handles.push(c.push(value));
+        }
+        assert_eq!(Some(10), a.pop());
+
+        b.meld(c);
+        a.meld(b);
+        assert_eq!(8, a.len());
+        assert_eq!(Some(&20), a.peek());
+
+        // the handles of the melded heaps still work
+        assert_eq!(None, a.get(handles[3]));
+        assert_eq!(Some(&90), a.get(handles[6]));
+        a.decrease_key(handles[6], 5).unwrap();
+        a.decrease_key(handles[8], 15).unwrap();
+        assert_eq!(Err(errordsa::Error::KeyIncreased), a.decrease_key(handles[0], 55));
+
+        let mut popped: Vec<u64> = vec![];
+        while let Some(value) = a.pop() {
+            popped.push(value);
+        }
+        assert_eq!(vec![5, 15, 20, 30, 50, 60, 70, 80], popped);
+    }
+
+    #[test]
+    fn pairing_heap() {
+        check_against_sorted_vec::<PairingHeap<u64>>();
+        check_meld::<PairingHeap<u64>>();
+    }
+
+    #[test]
+    fn binomial_heap() {
+        check_against_sorted_vec::<BinomialHeap<u64>>();
+        check_meld::<BinomialHeap<u64>>();
+    }
+
+    #[test]
+    fn fibonacci_heap() {
+        check_against_sorted_vec::<FibonacciHeap<u64>>();
+        check_meld::<FibonacciHeap<u64>>();
+    }
+}
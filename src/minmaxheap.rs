@@ -0,0 +1,256 @@
+#![allow(dead_code)]
+
+// A min-max heap is a binary heap whose levels alternate between min levels (the even ones, starting by the root) and
+// max levels. Every element of a min level is smaller than all its descendants, and every element of a max level
+// is bigger than all its descendants. So the smallest element is the root, and the biggest one is one of its
+// children, and both ends can be peeked in O(1) and popped in O(log n).
+// It's stored in a vec like BinaryHeap, and moving an element up or down jumps between grandparents and grandchildren,
+// which are in the same kind of level.
+
+pub struct MinMaxHeap<T: PartialOrd> {
+    arr: Vec<T>,
+}
+
+impl<T: PartialOrd> MinMaxHeap<T> {
+    pub fn new() -> MinMaxHeap<T> {
+        MinMaxHeap { arr: Vec::<T>::new() }
+    }
+
+    pub fn from_vec(vec: Vec<T>) -> MinMaxHeap<T> {
+        // like BinaryHeap::rebuild, every parent is pushed down, starting from the last one
+        let mut heap: MinMaxHeap<T> = MinMaxHeap { arr: vec };
+        for i in (0..heap.len() / 2).rev() {
+            heap.push_down(i);
+        }
+        heap
+    }
+
+    pub fn len(&self) -> usize {
+        self.arr.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.arr.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.arr.clear();
+    }
+
+    pub fn push(&mut self, value: T) {
+        self.arr.push(value);
+        self.bubble_up(self.len() - 1);
+    }
+
+    pub fn peek_min(&self) -> Option<&T> {
+        self.arr.first()
+    }
+
+    pub fn peek_max(&self) -> Option<&T> {
+        Some(&self.arr[self.max_index()?])
+    }
+
+    pub fn pop_min(&mut self) -> Option<T> {
+        self.remove(0)
+    }
+
+    pub fn pop_max(&mut self) -> Option<T> {
+        self.remove(self.max_index()?)
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        // in no particular order
+        self.arr
+    }
+
+    fn max_index(&self) -> Option<usize> {
+        // the root if it's alone, otherwise the biggest of its children
+        match self.len() {
+            0 => None,
+            1 => Some(0),
+            2 => Some(1),
+            _ => Some(if self.arr[2] > self.arr[1] { 2 } else { 1 }),
+        }
+    }
+
+    fn remove(&mut self, index: usize) -> Option<T> {
+        // the last element takes the place of the removed one, and is pushed down from there
+        if index >= self.len() {
+            return None;
+        }
+        let r: T = self.arr.swap_remove(index);
+        if index < self.len() {
+            self.push_down(index);
+        }
+        Some(r)
+    }
+
+    fn is_min_level(index: usize) -> bool {
+        // the level of an index is log2(index + 1)
+        (index + 1).ilog2().is_multiple_of(2)
+    }
+
+    fn parent(index: usize) -> usize {
+        (index - 1) / 2
+    }
+
+    fn first_child(index: usize) -> usize {
+        index * 2 + 1
+    }
+
+    fn before(&self, a: usize, b: usize, min: bool) -> bool {
+        // true if arr[a] must be above arr[b] in a min level (or a max level, if min is false)
+        if min {
+            self.arr[a] < self.arr[b]
+        } else {
+            self.arr[a] > self.arr[b]
+        }
+    }
+
+    fn bubble_up(&mut self, index: usize) {
+        if index == 0 {
+            return;
+        }
+        let min: bool = Self::is_min_level(index);
+        let parent: usize = Self::parent(index);
+        if self.before(parent, index, min) {
+            // the parent is in the other kind of level, and the element belongs there
+            self.arr.swap(index, parent);
+            self.bubble_up_grandparents(parent, !min);
+        } else {
+            self.bubble_up_grandparents(index, min);
+        }
+    }
+
+    fn bubble_up_grandparents(&mut self, index: usize, min: bool) {
+        let mut index: usize = index;
+        while index >= 3 {
+            let grandparent: usize = Self::parent(Self::parent(index));
+            if !self.before(index, grandparent, min) {
+                return;
+            }
+            self.arr.swap(index, grandparent);
+            index = grandparent;
+        }
+    }
+
+    fn push_down(&mut self, index: usize) {
+        let min: bool = Self::is_min_level(index);
+        let mut index: usize = index;
+        loop {
+            // the first element among the children and grandchildren
+            let first_child: usize = Self::first_child(index);
+            if first_child >= self.len() {
+                return;
+            }
+            let first_grandchild: usize = Self::first_child(first_child);
+            let mut m: usize = first_child;
+            let candidates = (first_child + 1..first_child + 2).chain(first_grandchild..first_grandchild + 4);
+            for i in candidates.filter(|i| *i < self.len()) {
+                if self.before(i, m, min) {
+                    m = i;
+                }
+            }
+
+            if !self.before(m, index, min) {
+                return;
+            }
+            self.arr.swap(m, index);
+            if m < first_grandchild {
+                // a child is in the other kind of level, so there's nothing below it to fix
+                return;
+            }
+            // the element that came down may belong in the level of its new parent
+            let parent: usize = Self::parent(m);
+            if self.before(parent, m, min) {
+                self.arr.swap(m, parent);
+            }
+            index = m;
+        }
+    }
+}
+
+impl<T: PartialOrd> Default for MinMaxHeap<T> {
+    fn default() -> MinMaxHeap<T> {
+        MinMaxHeap::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::next_random;
+
+    #[test]
+    fn min_max_heap_push_and_pop() {
+        let mut heap: MinMaxHeap<i32> = MinMaxHeap::new();
+        assert_eq!(None, heap.peek_max());
+        assert_eq!(None, heap.pop_min());
+
+        for value in [50, 20, 80, 10, 70, 30, 90, 60, 40] {
+            heap.push(value);
+        }
+        assert_eq!(Some(&10), heap.peek_min());
+        assert_eq!(Some(&90), heap.peek_max());
+
+        assert_eq!(Some(90), heap.pop_max());
+        assert_eq!(Some(10), heap.pop_min());
+        assert_eq!(Some(80), heap.pop_max());
+        assert_eq!(Some(70), heap.pop_max());
+        assert_eq!(Some(20), heap.pop_min());
+        assert_eq!(4, heap.len());
+        assert_eq!(Some(60), heap.pop_max());
+        assert_eq!(Some(30), heap.pop_min());
+        assert_eq!(Some(50), heap.pop_max());
+        assert_eq!(Some(&40), heap.peek_min());
+        assert_eq!(Some(&40), heap.peek_max());
+        assert_eq!(Some(40), heap.pop_max());
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn min_max_heap_against_sorted_vec() {
+        let mut seed: u64 = 5;
+        let mut heap: MinMaxHeap<u64> = MinMaxHeap::from_vec((0..50).map(|i| (i * 37) % 101).collect());
+        let mut sorted: Vec<u64> = heap.arr.clone();
+        sorted.sort();
+
+        for _ in 0..1000 {
+            match next_random(&mut seed) % 3 {
+                0 => {
+                    assert_eq!(sorted.first().copied(), heap.pop_min());
+                    if !sorted.is_empty() {
+                        sorted.remove(0);
+                    }
+                }
+                1 => {
+                    assert_eq!(sorted.pop(), heap.pop_max());
+                }
+                _ => {
+                    let value: u64 = next_random(&mut seed) % 100;
+                    heap.push(value);
+                    let i: usize = sorted.partition_point(|e| *e < value);
+                    sorted.insert(i, value);
+                }
+            }
+            assert_eq!(sorted.len(), heap.len());
+            assert_eq!(sorted.first(), heap.peek_min());
+            assert_eq!(sorted.last(), heap.peek_max());
+        }
+    }
+
+    #[test]
+    fn min_max_heap_bounded() {
+        // keeps the 3 biggest values, evicting the smallest one when it's full
+        let mut heap: MinMaxHeap<i32> = MinMaxHeap::new();
+        for value in [4, 9, 1, 7, 3, 8, 2] {
+            heap.push(value);
+            if heap.len() > 3 {
+                heap.pop_min();
+            }
+        }
+        let mut values: Vec<i32> = heap.into_vec();
+        values.sort();
+        assert_eq!(vec![7, 8, 9], values);
+    }
+}
@@ -0,0 +1,194 @@
+#![allow(dead_code)]
+
+// Common interface of the search trees used as sets of ordered values: binarytree::BinaryTree, Treap, SplayTree
+// and BTree. Unlike BinaryTree::insert_ordered, insert doesn't add a value that is already there.
+// contains and the queries take &mut self because the splay tree restructures itself on every access, which is what
+// makes them amortized O(log n) there.
+
+pub trait OrderedSet<T> {
+    // false if the value was already in the set
+    fn insert(&mut self, value: T) -> bool;
+
+    // false if the value wasn't in the set
+    fn remove(&mut self, value: &T) -> bool;
+
+    fn contains(&mut self, value: &T) -> bool;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn min(&mut self) -> Option<&T>;
+
+    fn max(&mut self) -> Option<&T>;
+
+    // the biggest value that is smaller than or equal to value
+    fn floor(&mut self, value: &T) -> Option<&T>;
+
+    // the smallest value that is bigger than or equal to value
+    fn ceiling(&mut self, value: &T) -> Option<&T>;
+
+    // the biggest value that is smaller than value
+    fn predecessor(&mut self, value: &T) -> Option<&T>;
+
+    // the smallest value that is bigger than value
+    fn successor(&mut self, value: &T) -> Option<&T>;
+
+    fn in_order_traverse<F: FnMut(&T)>(&self, visit_func: &mut F);
+}
+
+// The read-only queries and the traversals are the same walk for all of them (and for avltree::AvlTree), so the
+// trees only have to say how to read their nodes.
+
+pub trait SearchNode<T> {
+    fn value(&self) -> &T;
+
+    fn left(&self) -> Option<&Self>;
+
+    fn right(&self) -> Option<&Self>;
+}
+
+pub fn min<T, N: SearchNode<T>>(root: Option<&N>) -> Option<&T> {
+    let mut node: &N = root?;
+    while let Some(left) = node.left() {
+        node = left;
+    }
+    Some(node.value())
+}
+
+pub fn max<T, N: SearchNode<T>>(root: Option<&N>) -> Option<&T> {
+    let mut node: &N = root?;
+    while let Some(right) = node.right() {
+        node = right;
+    }
+    Some(node.value())
+}
+
+pub fn first_where<T, N: SearchNode<T>, F: Fn(&T) -> bool>(root: Option<&N>, is_after: F) -> Option<&T> {
+    // the first value for which is_after is true, when it's false for all the values before it and true after
+    let mut found: Option<&T> = None;
+    let mut current: Option<&N> = root;
+    while let Some(node) = current {
+        if is_after(node.value()) {
+            found = Some(node.value());
+            current = node.left();
+        } else {
+            current = node.right();
+        }
+    }
+    found
+}
+
+pub fn last_where<T, N: SearchNode<T>, F: Fn(&T) -> bool>(root: Option<&N>, is_before: F) -> Option<&T> {
+    // the last value for which is_before is true, when it's true for all the values before it and false after
+    let mut found: Option<&T> = None;
+    let mut current: Option<&N> = root;
+    while let Some(node) = current {
+        if is_before(node.value()) {
+            found = Some(node.value());
+            current = node.right();
+        } else {
+            current = node.left();
+        }
+    }
+    found
+}
+
+pub fn in_order_traverse<T, N: SearchNode<T>, F: FnMut(&T)>(root: Option<&N>, visit_func: &mut F) {
+    // with a stack instead of recursion, as the splay tree can be a single long path
+    let mut stack: Vec<&N> = vec![];
+    let mut current: Option<&N> = root;
+    loop {
+        while let Some(node) = current {
+            stack.push(node);
+            current = node.left();
+        }
+        let Some(node) = stack.pop() else {
+            return;
+        };
+        visit_func(node.value());
+        current = node.right();
+    }
+}
+
+pub fn pre_order_traverse<T, N: SearchNode<T>, F: FnMut(&T)>(root: Option<&N>, visit_func: &mut F) {
+    let mut stack: Vec<&N> = root.into_iter().collect();
+    while let Some(node) = stack.pop() {
+        visit_func(node.value());
+        // the right child is pushed first so the left subtree is visited first
+        stack.extend(node.right());
+        stack.extend(node.left());
+    }
+}
+
+pub fn post_order_traverse<T, N: SearchNode<T>, F: FnMut(&T)>(root: Option<&N>, visit_func: &mut F) {
+    // a pre-order walk that visits the right subtree first gives the post-order backwards
+    let mut stack: Vec<&N> = root.into_iter().collect();
+    let mut reversed: Vec<&N> = vec![];
+    while let Some(node) = stack.pop() {
+        reversed.push(node);
+        stack.extend(node.left());
+        stack.extend(node.right());
+    }
+    for node in reversed.into_iter().rev() {
+        visit_func(node.value());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+    use crate::{ binarytree::BinaryTree, btree::BTree, splaytree::SplayTree, testutil::next_random, treap::Treap };
+
+    fn check_against_btreeset<S: OrderedSet<u64> + Default>() {
+        let mut seed: u64 = 41;
+        let mut set: S = S::default();
+        let mut expected: BTreeSet<u64> = BTreeSet::new();
+        assert!(set.is_empty());
+
+        for _ in 0..2000 {
+            let value: u64 = next_random(&mut seed) % 200;
+            match next_random(&mut seed) % 3 {
+                0 => assert_eq!(expected.remove(&value), set.remove(&value)),
+                1 => assert_eq!(expected.insert(value), set.insert(value)),
+                _ => assert_eq!(expected.contains(&value), set.contains(&value)),
+            }
+            assert_eq!(expected.len(), set.len());
+
+            let x: u64 = next_random(&mut seed) % 210;
+            assert_eq!(expected.range(..=x).next_back(), set.floor(&x));
+            assert_eq!(expected.range(x..).next(), set.ceiling(&x));
+            assert_eq!(expected.range(..x).next_back(), set.predecessor(&x));
+            assert_eq!(expected.range(x + 1..).next(), set.successor(&x));
+        }
+
+        assert_eq!(expected.first(), set.min());
+        assert_eq!(expected.last(), set.max());
+        let mut values: Vec<u64> = vec![];
+        set.in_order_traverse(&mut (|value: &u64| values.push(*value)));
+        assert!(expected.iter().eq(values.iter()));
+    }
+
+    #[test]
+    fn binary_tree_set() {
+        check_against_btreeset::<BinaryTree<u64>>();
+    }
+
+    #[test]
+    fn treap_set() {
+        check_against_btreeset::<Treap<u64>>();
+    }
+
+    #[test]
+    fn splay_tree_set() {
+        check_against_btreeset::<SplayTree<u64>>();
+    }
+
+    #[test]
+    fn b_tree_set() {
+        check_against_btreeset::<BTree<u64, 4>>();
+    }
+}
@@ -0,0 +1,180 @@
+#![allow(dead_code)]
+
+use crate::{ errordsa, mergeableheap::{ shift, Arena, Handle, PriorityQueue } };
+
+// A pairing heap is a single tree where every node is smaller than its children, which can have any amount of them.
+// Every child list is a linked list: a node points to its leftmost child and to its right sibling.
+// Melding two trees is just making the bigger root the leftmost child of the other one, and everything else is
+// built on top of that. Popping links the children of the root in pairs from left to right, and then links those
+// pairs from right to left, which is what keeps the operations cheap in the long run (amortized O(log n) pop).
+
+struct Node<T> {
+    value: T,
+    child: Option<usize>, // leftmost child
+    sibling: Option<usize>, // right sibling
+    prev: Option<usize>, // left sibling, or the parent for the leftmost child
+}
+
+pub struct PairingHeap<T> {
+    nodes: Arena<Node<T>>,
+    root: Option<usize>,
+}
+
+impl<T: PartialOrd> PairingHeap<T> {
+    pub fn new() -> PairingHeap<T> {
+        PairingHeap {
+            nodes: Arena::new(),
+            root: None,
+        }
+    }
+
+    fn link(&mut self, a: usize, b: usize) -> usize {
+        // the root with the bigger value becomes the leftmost child of the other one, which is returned
+        let (parent, child) = if self.nodes[b].value < self.nodes[a].value { (b, a) } else { (a, b) };
+        let first: Option<usize> = self.nodes[parent].child;
+        if let Some(first) = first {
+            self.nodes[first].prev = Some(child);
+        }
+        self.nodes[child].sibling = first;
+        self.nodes[child].prev = Some(parent);
+        self.nodes[parent].child = Some(child);
+        parent
+    }
+
+    fn link_roots(&mut self, a: Option<usize>, b: Option<usize>) -> Option<usize> {
+        match (a, b) {
+            (Some(a), Some(b)) => Some(self.link(a, b)),
+            (a, None) => a,
+            (None, b) => b,
+        }
+    }
+
+    fn cut(&mut self, index: usize) {
+        // detaches the subtree of a node from its parent
+        let prev: usize = self.nodes[index].prev.unwrap();
+        let sibling: Option<usize> = self.nodes[index].sibling;
+        if self.nodes[prev].child == Some(index) {
+            self.nodes[prev].child = sibling;
+        } else {
+            self.nodes[prev].sibling = sibling;
+        }
+        if let Some(sibling) = sibling {
+            self.nodes[sibling].prev = Some(prev);
+        }
+        self.nodes[index].prev = None;
+        self.nodes[index].sibling = None;
+    }
+}
+
+impl<T: PartialOrd> PriorityQueue<T> for PairingHeap<T> {
+    type Handle = Handle;
+
+    fn push(&mut self, value: T) -> Handle {
+        let index: usize = self.nodes.insert(Node {
+            value,
+            child: None,
+            sibling: None,
+            prev: None,
+        });
+        self.root = self.link_roots(self.root, Some(index));
+        self.nodes.handle(index)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        Some(&self.nodes[self.root?].value)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        let root: usize = self.root?;
+
+        let mut children: Vec<usize> = vec![];
+        let mut child: Option<usize> = self.nodes[root].child;
+        while let Some(c) = child {
+            child = self.nodes[c].sibling;
+            self.nodes[c].prev = None;
+            self.nodes[c].sibling = None;
+            children.push(c);
+        }
+
+        // first pass: left to right, in pairs
+        let mut pairs: Vec<usize> = vec![];
+        for pair in children.chunks(2) {
+            match pair {
+                [a, b] => pairs.push(self.link(*a, *b)),
+                _ => pairs.push(pair[0]),
+            }
+        }
+        // second pass: right to left, all of them into one tree
+        let mut new_root: Option<usize> = None;
+        while let Some(tree) = pairs.pop() {
+            new_root = self.link_roots(new_root, Some(tree));
+        }
+
+        self.root = new_root;
+        Some(self.nodes.remove(root).value)
+    }
+
+    fn get(&self, handle: Handle) -> Option<&T> {
+        Some(&self.nodes[self.nodes.resolve(handle)?].value)
+    }
+
+    fn decrease_key(&mut self, handle: Handle, value: T) -> Result<(), errordsa::Error> {
+        let index: usize = self.nodes.resolve(handle).ok_or(errordsa::Error::InvalidHandle)?;
+        if value > self.nodes[index].value {
+            return Err(errordsa::Error::KeyIncreased);
+        }
+        self.nodes[index].value = value;
+        if self.root != Some(index) {
+            // the node may now be smaller than its parent, so its subtree is cut and melded with the root
+            self.cut(index);
+            self.root = self.link_roots(self.root, Some(index));
+        }
+        Ok(())
+    }
+
+    fn meld(&mut self, other: PairingHeap<T>) {
+        let offset: usize = self.nodes.append(other.nodes, |node, offset| {
+            shift(&mut node.child, offset);
+            shift(&mut node.sibling, offset);
+            shift(&mut node.prev, offset);
+        });
+        self.root = self.link_roots(self.root, other.root.map(|root| root + offset));
+    }
+
+    fn len(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+impl<T: PartialOrd> Default for PairingHeap<T> {
+    fn default() -> PairingHeap<T> {
+        PairingHeap::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_pop_and_decrease() {
+        let mut heap: PairingHeap<i32> = PairingHeap::new();
+        let a: Handle = heap.push(30);
+        heap.push(10);
+        let c: Handle = heap.push(20);
+        heap.push(40);
+
+        assert_eq!(Some(&10), heap.peek());
+        heap.decrease_key(a, 5).unwrap();
+        assert_eq!(Some(&5), heap.peek());
+        assert_eq!(Some(5), heap.pop());
+        assert_eq!(Some(10), heap.pop());
+
+        heap.decrease_key(c, 20).unwrap();
+        assert_eq!(Some(&20), heap.get(c));
+        assert_eq!(Some(20), heap.pop());
+        assert_eq!(Some(40), heap.pop());
+        assert_eq!(None, heap.pop());
+        assert_eq!(None, heap.peek());
+    }
+}
@@ -0,0 +1,469 @@
+#![allow(dead_code)]
+
+use std::{ cmp::Ordering, mem, ops::{ Bound, RangeBounds } };
+
+// Ordered map on a left-leaning red-black tree. A red-black tree is a 2-3 tree (where nodes have one or two keys)
+// written as a binary tree: a node with two keys is two binary nodes joined by a red link, and all the other links
+// are black. In the left-leaning variant red links always go to the left child, which leaves fewer cases to handle.
+// Since all the leaves of a 2-3 tree are at the same depth, every path from the root has the same amount of black
+// links, and there are never two red links in a row, so the height is at most 2 log(n).
+// Like in binarytree::BinaryTree, the nodes are boxes and the operations are recursive. Insertions and deletions go
+// down the tree, and fix the colors on the way back up with rotations and color flips.
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    left: Link<K, V>,
+    right: Link<K, V>,
+    red: bool, // color of the link from the parent
+}
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+fn is_red<K, V>(node: &Link<K, V>) -> bool {
+    node.as_ref().is_some_and(|n| n.red)
+}
+
+impl<K: Ord, V> Node<K, V> {
+    fn new(key: K, value: V) -> Node<K, V> {
+        Node {
+            key,
+            value,
+            left: None,
+            right: None,
+            red: true,
+        }
+    }
+
+    fn rotate_left(mut this: Box<Node<K, V>>) -> Box<Node<K, V>> {
+        // the red right link of this becomes a left link
+        let mut right: Box<Node<K, V>> = this.right.take().unwrap();
+        this.right = right.left.take();
+        right.red = this.red;
+        this.red = true;
+        right.left = Some(this);
+        right
+    }
+
+    fn rotate_right(mut this: Box<Node<K, V>>) -> Box<Node<K, V>> {
+        let mut left: Box<Node<K, V>> = this.left.take().unwrap();
+        this.left = left.right.take();
+        left.red = this.red;
+        this.red = true;
+        left.right = Some(this);
+        left
+    }
+
+    fn flip_colors(&mut self) {
+        // splits a 4-node (both children red) or, the other way around, joins the node with its children
+        self.red = !self.red;
+        if let Some(left) = self.left.as_mut() {
+            left.red = !left.red;
+        }
+        if let Some(right) = self.right.as_mut() {
+            right.red = !right.red;
+        }
+    }
+
+    fn balance(mut this: Box<Node<K, V>>) -> Box<Node<K, V>> {
+        if is_red(&this.right) && !is_red(&this.left) {
+            this = Self::rotate_left(this);
+        }
+        if is_red(&this.left) && is_red(&this.left.as_ref().unwrap().left) {
+            this = Self::rotate_right(this);
+        }
+        if is_red(&this.left) && is_red(&this.right) {
+            this.flip_colors();
+        }
+        this
+    }
+
+    fn insert(this: Link<K, V>, key: K, value: V) -> (Box<Node<K, V>>, Option<V>) {
+        let mut this: Box<Node<K, V>> = match this {
+            None => {
+                return (Box::new(Node::new(key, value)), None);
+            }
+            Some(this) => this,
+        };
+        let old: Option<V>;
+        match key.cmp(&this.key) {
+            Ordering::Less => {
+                let (left, o) = Self::insert(this.left.take(), key, value);
+                this.left = Some(left);
+                old = o;
+            }
+            Ordering::Greater => {
+                let (right, o) = Self::insert(this.right.take(), key, value);
+                this.right = Some(right);
+                old = o;
+            }
+            Ordering::Equal => {
+                old = Some(mem::replace(&mut this.value, value));
+            }
+        }
+        (Self::balance(this), old)
+    }
+
+    fn move_red_left(mut this: Box<Node<K, V>>) -> Box<Node<K, V>> {
+        // makes this.left or one of its children red, so there's something to delete from on the left
+        this.flip_colors();
+        if is_red(&this.right.as_ref().unwrap().left) {
+            this.right = Some(Self::rotate_right(this.right.take().unwrap()));
+            this = Self::rotate_left(this);
+            this.flip_colors();
+        }
+        this
+    }
+
+    fn move_red_right(mut this: Box<Node<K, V>>) -> Box<Node<K, V>> {
+        this.flip_colors();
+        if is_red(&this.left.as_ref().unwrap().left) {
+            this = Self::rotate_right(this);
+            this.flip_colors();
+        }
+        this
+    }
+
+    fn remove_min(mut this: Box<Node<K, V>>) -> (Link<K, V>, (K, V)) {
+        // returns the subtree without its smallest node, and the key and value of that node
+        if this.left.is_none() {
+            return (None, (this.key, this.value));
+        }
+        if !is_red(&this.left) && !is_red(&this.left.as_ref().unwrap().left) {
+            this = Self::move_red_left(this);
+        }
+        let (left, min) = Self::remove_min(this.left.take().unwrap());
+        this.left = left;
+        (Some(Self::balance(this)), min)
+    }
+
+    fn remove(mut this: Box<Node<K, V>>, key: &K) -> (Link<K, V>, V) {
+        // the key must be in the subtree
+        if *key < this.key {
+            if !is_red(&this.left) && !is_red(&this.left.as_ref().unwrap().left) {
+                this = Self::move_red_left(this);
+            }
+            let (left, value) = Self::remove(this.left.take().unwrap(), key);
+            this.left = left;
+            return (Some(Self::balance(this)), value);
+        }
+
+        if is_red(&this.left) {
+            this = Self::rotate_right(this);
+        }
+        if *key == this.key && this.right.is_none() {
+            return (None, this.value);
+        }
+        if !is_red(&this.right) && !is_red(&this.right.as_ref().unwrap().left) {
+            this = Self::move_red_right(this);
+        }
+        let value: V;
+        if *key == this.key {
+            // the successor takes the place of the removed node
+            let (right, (min_key, min_value)) = Self::remove_min(this.right.take().unwrap());
+            this.right = right;
+            this.key = min_key;
+            value = mem::replace(&mut this.value, min_value);
+        } else {
+            let (right, v) = Self::remove(this.right.take().unwrap(), key);
+            this.right = right;
+            value = v;
+        }
+        (Some(Self::balance(this)), value)
+    }
+
+    fn check(&self, min: Option<&K>, max: Option<&K>) -> Result<usize, String> {
+        // checks the subtree and returns the amount of black links from it to the leaves
+        if min.is_some_and(|min| self.key <= *min) || max.is_some_and(|max| self.key >= *max) {
+            return Err("the keys are out of order".to_owned());
+        }
+        if is_red(&self.right) {
+            return Err("a right link is red".to_owned());
+        }
+        if self.red && is_red(&self.left) {
+            return Err("there are two red links in a row".to_owned());
+        }
+        let left: usize = match self.left.as_ref() {
+            None => 0,
+            Some(t) => t.check(min, Some(&self.key))?,
+        };
+        let right: usize = match self.right.as_ref() {
+            None => 0,
+            Some(t) => t.check(Some(&self.key), max)?,
+        };
+        if left != right {
+            return Err("the paths to the leaves have different amounts of black links".to_owned());
+        }
+        Ok(left + if self.red { 0 } else { 1 })
+    }
+}
+
+pub struct RedBlackMap<K, V> {
+    root: Link<K, V>,
+    len: usize,
+}
+
+impl<K: Ord, V> RedBlackMap<K, V> {
+    pub fn new() -> RedBlackMap<K, V> {
+        RedBlackMap {
+            root: None,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        // if the key was already there, its value is replaced and the old one is returned
+        let (mut root, old) = Node::insert(self.root.take(), key, value);
+        root.red = false;
+        self.root = Some(root);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let mut current: Option<&Box<Node<K, V>>> = self.root.as_ref();
+        while let Some(node) = current {
+            match key.cmp(&node.key) {
+                Ordering::Less => {
+                    current = node.left.as_ref();
+                }
+                Ordering::Greater => {
+                    current = node.right.as_ref();
+                }
+                Ordering::Equal => {
+                    return Some(&node.value);
+                }
+            }
+        }
+        None
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let mut current: Option<&mut Box<Node<K, V>>> = self.root.as_mut();
+        while let Some(node) = current {
+            match key.cmp(&node.key) {
+                Ordering::Less => {
+                    current = node.left.as_mut();
+                }
+                Ordering::Greater => {
+                    current = node.right.as_mut();
+                }
+                Ordering::Equal => {
+                    return Some(&mut node.value);
+                }
+            }
+        }
+        None
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        if !self.contains_key(key) {
+            return None;
+        }
+        let mut root: Box<Node<K, V>> = self.root.take().unwrap();
+        if !is_red(&root.left) && !is_red(&root.right) {
+            // so that there's a red link to delete from at the top
+            root.red = true;
+        }
+        let (root, value) = Node::remove(root, key);
+        self.root = root;
+        if let Some(root) = self.root.as_mut() {
+            root.red = false;
+        }
+        self.len -= 1;
+        Some(value)
+    }
+
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        let mut node: &Node<K, V> = self.root.as_ref()?;
+        while let Some(left) = node.left.as_ref() {
+            node = left;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        let mut node: &Node<K, V> = self.root.as_ref()?;
+        while let Some(right) = node.right.as_ref() {
+            node = right;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    pub fn iter(&self) -> Range<'_, K, V, (Bound<K>, Bound<K>)> {
+        self.range((Bound::Unbounded, Bound::Unbounded))
+    }
+
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V, R> {
+        // in order of the keys. The stack starts with the path to the first key in the range
+        let mut stack: Vec<&Node<K, V>> = vec![];
+        let mut current: Option<&Box<Node<K, V>>> = self.root.as_ref();
+        while let Some(node) = current {
+            let after_start: bool = match range.start_bound() {
+                Bound::Included(start) => node.key >= *start,
+                Bound::Excluded(start) => node.key > *start,
+                Bound::Unbounded => true,
+            };
+            if after_start {
+                stack.push(node);
+                current = node.left.as_ref();
+            } else {
+                current = node.right.as_ref();
+            }
+        }
+        Range { stack, range }
+    }
+
+    pub fn check_invariants(&self) -> Result<(), String> {
+        // the keys are ordered, and the tree is a valid left-leaning red-black tree
+        if let Some(root) = self.root.as_ref() {
+            if root.red {
+                return Err("the root is red".to_owned());
+            }
+            root.check(None, None)?;
+        }
+        Ok(())
+    }
+}
+
+impl<K: Ord, V> Default for RedBlackMap<K, V> {
+    fn default() -> RedBlackMap<K, V> {
+        RedBlackMap::new()
+    }
+}
+
+pub struct Range<'a, K, V, R> {
+    stack: Vec<&'a Node<K, V>>, // the next node, and the ancestors that come after it
+    range: R,
+}
+
+impl<'a, K: Ord, V, R: RangeBounds<K>> Iterator for Range<'a, K, V, R> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        let node: &'a Node<K, V> = self.stack.pop()?;
+        let before_end: bool = match self.range.end_bound() {
+            Bound::Included(end) => node.key <= *end,
+            Bound::Excluded(end) => node.key < *end,
+            Bound::Unbounded => true,
+        };
+        if !before_end {
+            self.stack.clear();
+            return None;
+        }
+        // the keys that come right after this one are the leftmost path of its right subtree
+        let mut current: Option<&'a Box<Node<K, V>>> = node.right.as_ref();
+        while let Some(n) = current {
+            self.stack.push(n);
+            current = n.left.as_ref();
+        }
+        Some((&node.key, &node.value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use crate::testutil::next_random;
+
+    #[test]
+    fn red_black_map() {
+        let mut map: RedBlackMap<i32, &str> = RedBlackMap::new();
+        assert_eq!(None, map.first_key_value());
+        assert_eq!(None, map.insert(5, "five"));
+        assert_eq!(None, map.insert(1, "one"));
+        assert_eq!(None, map.insert(9, "nine"));
+        assert_eq!(Some("five"), map.insert(5, "FIVE"));
+        assert_eq!(3, map.len());
+        map.check_invariants().unwrap();
+
+        assert_eq!(Some(&"FIVE"), map.get(&5));
+        assert_eq!(None, map.get(&4));
+        *map.get_mut(&9).unwrap() = "NINE";
+        assert_eq!(Some((&1, &"one")), map.first_key_value());
+        assert_eq!(Some((&9, &"NINE")), map.last_key_value());
+
+        assert_eq!(Some("one"), map.remove(&1));
+        assert_eq!(None, map.remove(&1));
+        assert_eq!(2, map.len());
+        map.check_invariants().unwrap();
+        let entries: Vec<(&i32, &&str)> = map.iter().collect();
+        assert_eq!(vec![(&5, &"FIVE"), (&9, &"NINE")], entries);
+    }
+
+    fn keys<R: RangeBounds<i32>>(range: Range<'_, i32, i32, R>) -> Vec<i32> {
+        range.map(|(key, _)| *key).collect()
+    }
+
+    #[test]
+    fn red_black_map_range() {
+        let mut map: RedBlackMap<i32, i32> = RedBlackMap::new();
+        for key in 0..20 {
+            map.insert(key * 2, key);
+        }
+        assert_eq!(vec![6, 8, 10], keys(map.range(5..12)));
+        assert_eq!(vec![6, 8, 10, 12], keys(map.range(6..=12)));
+        assert_eq!(vec![34, 36, 38], keys(map.range(33..)));
+        assert_eq!(vec![0, 2], keys(map.range(..4)));
+        assert_eq!(Vec::<i32>::new(), keys(map.range(40..)));
+        assert_eq!(20, map.iter().count());
+    }
+
+    #[test]
+    fn red_black_map_sorted_insertions() {
+        let mut map: RedBlackMap<u32, u32> = RedBlackMap::new();
+        for key in 0..10_000 {
+            map.insert(key, key);
+        }
+        map.check_invariants().unwrap();
+        for key in (0..10_000).step_by(2) {
+            map.remove(&key);
+        }
+        map.check_invariants().unwrap();
+        assert_eq!(5000, map.len());
+        assert_eq!(Some((&1, &1)), map.first_key_value());
+    }
+
+    #[test]
+    fn red_black_map_against_btreemap() {
+        let mut seed: u64 = 31;
+        let mut map: RedBlackMap<u64, u64> = RedBlackMap::new();
+        let mut expected: BTreeMap<u64, u64> = BTreeMap::new();
+        for i in 0..3000 {
+            let key: u64 = next_random(&mut seed) % 300;
+            match next_random(&mut seed) % 3 {
+                0 => assert_eq!(expected.remove(&key), map.remove(&key)),
+                1 => assert_eq!(expected.insert(key, i), map.insert(key, i)),
+                _ => {
+                    if let Some(value) = map.get_mut(&key) {
+                        *value += 1;
+                    }
+                    if let Some(value) = expected.get_mut(&key) {
+                        *value += 1;
+                    }
+                }
+            }
+            map.check_invariants().unwrap();
+            assert_eq!(expected.len(), map.len());
+            assert_eq!(expected.first_key_value(), map.first_key_value());
+            assert_eq!(expected.last_key_value(), map.last_key_value());
+        }
+        assert!(expected.iter().eq(map.iter()));
+        assert!(expected.range(50..150).eq(map.range(50..150)));
+    }
+}
@@ -1,63 +1,426 @@
 #![allow(dead_code)]
 
-pub struct RingBuffer<T: Copy> {
-    buffer: Vec<Option<T>>, // TODO: learn about memory allocation in rust and do it on my own
+use std::{ iter::Chain, mem::{ self, MaybeUninit }, ops::{ Index, IndexMut, Range }, ptr, slice };
+
+use crate::errordsa;
+
+// The buffer manages the initialization of its slots by hand: only the `length` slots starting at `start`
+// (wrapping around the end) hold a value, the rest are uninitialized memory.
+// This way there's no need for an Option (or a Default value) in every slot, and popped values are moved out
+// instead of being left behind. Because of this, the buffer has to drop the values it still holds by itself.
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Mode {
+    Growable, // the buffer doubles its capacity when it's full
+    Fixed, // pushing to a full buffer returns an error
+    Overwrite, // pushing to a full buffer evicts the element at the other end
+}
+
+pub struct RingBuffer<T> {
+    buffer: Box<[MaybeUninit<T>]>,
     start: usize,
     length: usize,
     capacity: usize,
+    mode: Mode,
 }
 
-impl<T: Copy> RingBuffer<T> {
+impl<T> RingBuffer<T> {
     pub fn new(capacity: usize) -> RingBuffer<T> {
-        let mut vec = Vec::<Option<T>>::with_capacity(capacity);
-        for _ in 0..capacity {
-            vec.push(None);
-        }
+        RingBuffer::with_mode(capacity, Mode::Growable)
+    }
+
+    pub fn with_capacity(capacity: usize) -> RingBuffer<T> {
+        RingBuffer::with_mode(capacity, Mode::Growable)
+    }
+
+    pub fn with_mode(capacity: usize, mode: Mode) -> RingBuffer<T> {
         RingBuffer {
-            buffer: vec,
+            buffer: RingBuffer::allocate(capacity),
             start: 0,
             length: 0,
             capacity,
+            mode,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    pub fn push_back(&mut self, value: T) -> Result<Option<T>, errordsa::Error> {
+        // returns the evicted element when a full buffer is in Overwrite mode
+        if self.length == self.capacity {
+            match self.mode {
+                Mode::Growable => self.resize(usize::max(1, self.capacity * 2)),
+                Mode::Fixed => {
+                    return Err(errordsa::Error::BufferFull);
+                }
+                Mode::Overwrite => {
+                    if self.capacity == 0 {
+                        return Ok(Some(value));
+                    }
+                    // SAFETY: the buffer is full, so the front slot is initialized
+                    let evicted: T = mem::replace(unsafe { self.buffer[self.start].assume_init_mut() }, value);
+                    self.start = (self.start + 1) % self.capacity;
+                    return Ok(Some(evicted));
+                }
+            }
         }
+        let index: usize = self.physical_index(self.length);
+        self.buffer[index].write(value);
+        self.length += 1;
+        Ok(None)
     }
 
-    pub fn push_back(&mut self, value: T) {
+    pub fn push_front(&mut self, value: T) -> Result<Option<T>, errordsa::Error> {
+        // returns the evicted element when a full buffer is in Overwrite mode
         if self.length == self.capacity {
-            panic!("RingBuffer has overflowed its capacity"); // actually it should resize de buffer
+            match self.mode {
+                Mode::Growable => self.resize(usize::max(1, self.capacity * 2)),
+                Mode::Fixed => {
+                    return Err(errordsa::Error::BufferFull);
+                }
+                Mode::Overwrite => {
+                    if self.capacity == 0 {
+                        return Ok(Some(value));
+                    }
+                    // when the buffer is full, the slot before the front is the back
+                    self.start = (self.start + self.capacity - 1) % self.capacity;
+                    // SAFETY: the buffer is full, so the back slot is initialized
+                    let evicted: T = mem::replace(unsafe { self.buffer[self.start].assume_init_mut() }, value);
+                    return Ok(Some(evicted));
+                }
+            }
         }
-        self.buffer[(self.start + self.length) % self.capacity] = Some(value);
+        self.start = (self.start + self.capacity - 1) % self.capacity;
+        self.buffer[self.start].write(value);
         self.length += 1;
+        Ok(None)
     }
 
     pub fn pop_front(&mut self) -> Option<T> {
         if self.length == 0 {
             return None;
         }
-        self.start += 1;
+        // SAFETY: the front slot is initialized, and it's no longer considered so once start moves past it
+        let value: T = unsafe { self.buffer[self.start].assume_init_read() };
+        self.start = (self.start + 1) % self.capacity;
+        self.length -= 1;
+        Some(value)
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.length == 0 {
+            return None;
+        }
         self.length -= 1;
-        return self.buffer[(self.start - 1) % self.capacity]; // it could be overwritten with None, but it's not necessary
+        // SAFETY: the back slot is initialized, and it's no longer considered so once length decreases
+        Some(unsafe { self.buffer[self.physical_index(self.length)].assume_init_read() })
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(0)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        if self.length == 0 {
+            return None;
+        }
+        self.get(self.length - 1)
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        if self.length == 0 {
+            return None;
+        }
+        self.get_mut(self.length - 1)
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        // index 0 is the front of the buffer
+        if index >= self.length {
+            return None;
+        }
+        // SAFETY: every index below length maps to an initialized slot
+        Some(unsafe { self.buffer[self.physical_index(index)].assume_init_ref() })
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.length {
+            return None;
+        }
+        let index: usize = self.physical_index(index);
+        // SAFETY: every index below length maps to an initialized slot
+        Some(unsafe { self.buffer[index].assume_init_mut() })
+    }
+
+    pub fn iter(&self) -> Chain<slice::Iter<'_, T>, slice::Iter<'_, T>> {
+        let (a, b) = self.as_slices();
+        a.iter().chain(b.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> Chain<slice::IterMut<'_, T>, slice::IterMut<'_, T>> {
+        let (a, b) = self.as_mut_slices();
+        a.iter_mut().chain(b.iter_mut())
+    }
+
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        // the first slice goes from the front to the end of the buffer, the second one is the wrapped part
+        let (front, wrapped) = self.initialized_ranges();
+        // SAFETY: both ranges only cover initialized slots
+        unsafe { (assume_init_slice(&self.buffer[front]), assume_init_slice(&self.buffer[wrapped])) }
+    }
+
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        let (front, wrapped) = self.initialized_ranges();
+        let (head, tail) = self.buffer.split_at_mut(front.start);
+        // SAFETY: both ranges only cover initialized slots, and the wrapped one ends before the front one starts
+        unsafe { (assume_init_slice_mut(&mut tail[..front.len()]), assume_init_slice_mut(&mut head[wrapped])) }
+    }
+
+    pub fn make_contiguous(&mut self) -> &mut [T] {
+        // rotating the whole buffer brings the front to index 0 and the wrapped part right after it, without
+        // allocating. The slots are MaybeUninit, so moving them around never reads or drops a value
+        if self.start + self.length > self.capacity {
+            self.buffer.rotate_left(self.start);
+            self.start = 0;
+        }
+        self.as_mut_slices().0
+    }
+
+    pub fn rotate_left(&mut self, n: usize) {
+        // the first n elements are moved to the back
+        assert!(n <= self.length, "RingBuffer can't be rotated by more than its length");
+        if n > self.length / 2 {
+            self.rotate_right(self.length - n);
+            return;
+        }
+        for _ in 0..n {
+            let index: usize = self.physical_index(self.length);
+            // SAFETY: the value is moved from the front slot to the one after the back, and the front slot
+            // stops being initialized when start moves past it (if the buffer is full both slots are the same)
+            unsafe {
+                let value: T = self.buffer[self.start].assume_init_read();
+                self.buffer[index].write(value);
+            }
+            self.start = (self.start + 1) % self.capacity;
+        }
+    }
+
+    pub fn rotate_right(&mut self, n: usize) {
+        // the last n elements are moved to the front
+        assert!(n <= self.length, "RingBuffer can't be rotated by more than its length");
+        if n > self.length / 2 {
+            self.rotate_left(self.length - n);
+            return;
+        }
+        for _ in 0..n {
+            let back: usize = self.physical_index(self.length - 1);
+            self.start = (self.start + self.capacity - 1) % self.capacity;
+            // SAFETY: the same as in rotate_left, but the other way around
+            unsafe {
+                let value: T = self.buffer[back].assume_init_read();
+                self.buffer[self.start].write(value);
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        let (front, wrapped) = self.as_mut_slices();
+        let front: *mut [T] = front;
+        let wrapped: *mut [T] = wrapped;
+        // the buffer is emptied before dropping, so a panicking drop can only leak the values, never drop them twice
+        self.start = 0;
+        self.length = 0;
+        // SAFETY: the slices were initialized and are no longer reachable through the buffer
+        unsafe {
+            ptr::drop_in_place(front);
+            ptr::drop_in_place(wrapped);
+        }
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        // makes room for at least `additional` more elements, even in Fixed mode. Like Vec::reserve, it at least
+        // doubles the capacity, so reserving in a loop stays amortized O(1) per element
+        if self.capacity - self.length < additional {
+            self.resize(usize::max(self.length + additional, self.capacity * 2));
+        }
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        if self.capacity > self.length {
+            self.resize(self.length);
+        }
+    }
+
+    fn allocate(capacity: usize) -> Box<[MaybeUninit<T>]> {
+        (0..capacity).map(|_| MaybeUninit::uninit()).collect()
+    }
+
+    fn physical_index(&self, index: usize) -> usize {
+        (self.start + index) % self.capacity
+    }
+
+    fn initialized_ranges(&self) -> (Range<usize>, Range<usize>) {
+        if self.start + self.length <= self.capacity {
+            return (self.start..self.start + self.length, 0..0);
+        }
+        (self.start..self.capacity, 0..self.start + self.length - self.capacity)
+    }
+
+    fn resize(&mut self, new_capacity: usize) {
+        // the elements are moved in order to the new buffer, so the front ends up at index 0
+        let mut buffer: Box<[MaybeUninit<T>]> = RingBuffer::allocate(new_capacity);
+        let (front, wrapped) = self.initialized_ranges();
+        // SAFETY: the initialized slots are moved bit by bit into the new buffer, and the old one is then
+        // freed without dropping anything, since MaybeUninit never drops its contents
+        unsafe {
+            let src: *const MaybeUninit<T> = self.buffer.as_ptr();
+            let dst: *mut MaybeUninit<T> = buffer.as_mut_ptr();
+            ptr::copy_nonoverlapping(src.add(front.start), dst, front.len());
+            ptr::copy_nonoverlapping(src, dst.add(front.len()), wrapped.len());
+        }
+        self.buffer = buffer;
+        self.start = 0;
+        self.capacity = new_capacity;
+    }
+}
+
+impl<T: Clone> RingBuffer<T> {
+    pub fn latest(&self, n: usize) -> Vec<T> {
+        // the most recent n elements, from oldest to newest
+        let n: usize = usize::min(n, self.length);
+        self.iter()
+            .skip(self.length - n)
+            .cloned()
+            .collect()
+    }
+}
+
+impl<T> Drop for RingBuffer<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+// SAFETY (for both functions): every element of the slice must be initialized
+unsafe fn assume_init_slice<T>(slice: &[MaybeUninit<T>]) -> &[T] {
+    unsafe { &*(slice as *const [MaybeUninit<T>] as *const [T]) }
+}
+
+unsafe fn assume_init_slice_mut<T>(slice: &mut [MaybeUninit<T>]) -> &mut [T] {
+    unsafe { &mut *(slice as *mut [MaybeUninit<T>] as *mut [T]) }
+}
+
+impl<T> Index<usize> for RingBuffer<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        match self.get(index) {
+            Some(value) => value,
+            None => panic!("RingBuffer index out of bounds"),
+        }
+    }
+}
+
+impl<T> IndexMut<usize> for RingBuffer<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        match self.get_mut(index) {
+            Some(value) => value,
+            None => panic!("RingBuffer index out of bounds"),
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a RingBuffer<T> {
+    type Item = &'a T;
+    type IntoIter = Chain<slice::Iter<'a, T>, slice::Iter<'a, T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+pub struct IntoIter<T> {
+    buffer: RingBuffer<T>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.buffer.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.buffer.len(), Some(self.buffer.len()))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.buffer.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> IntoIterator for RingBuffer<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { buffer: self }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::{ cell::Cell, rc::Rc };
+
     use super::*;
 
+    struct DropCounter {
+        drops: Rc<Cell<usize>>,
+    }
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+        }
+    }
+
     #[test]
     fn push_and_pop() {
         let mut rf: RingBuffer<i32> = RingBuffer::new(5);
 
-        rf.push_back(0);
-        rf.push_back(1);
-        rf.push_back(2);
-        rf.push_back(3);
+        rf.push_back(0).unwrap();
+        rf.push_back(1).unwrap();
+        rf.push_back(2).unwrap();
+        rf.push_back(3).unwrap();
 
         assert_eq!(0, rf.pop_front().unwrap());
         assert_eq!(1, rf.pop_front().unwrap());
 
-        rf.push_back(4);
-        rf.push_back(5);
-        rf.push_back(6);
+        rf.push_back(4).unwrap();
+        rf.push_back(5).unwrap();
+        rf.push_back(6).unwrap();
 
         assert_eq!(2, rf.pop_front().unwrap());
         assert_eq!(3, rf.pop_front().unwrap());
@@ -65,4 +428,324 @@ mod tests {
         assert_eq!(5, rf.pop_front().unwrap());
         assert_eq!(6, rf.pop_front().unwrap());
     }
+
+    #[test]
+    fn grow_when_full() {
+        let mut rf: RingBuffer<i32> = RingBuffer::new(3);
+
+        rf.push_back(0).unwrap();
+        rf.push_back(1).unwrap();
+        rf.push_back(2).unwrap();
+        assert_eq!(Some(0), rf.pop_front());
+
+        // the buffer has wrapped around, so growing must keep the order
+        rf.push_back(3).unwrap();
+        rf.push_back(4).unwrap();
+        assert_eq!(6, rf.capacity());
+
+        assert_eq!(Some(1), rf.pop_front());
+        assert_eq!(Some(2), rf.pop_front());
+        assert_eq!(Some(3), rf.pop_front());
+        assert_eq!(Some(4), rf.pop_front());
+        assert_eq!(None, rf.pop_front());
+
+        let mut rf: RingBuffer<i32> = RingBuffer::new(0);
+        for i in 0..10 {
+            rf.push_back(i).unwrap();
+        }
+        for i in 0..10 {
+            assert_eq!(Some(i), rf.pop_front());
+        }
+    }
+
+    #[test]
+    fn fixed_capacity() {
+        let mut rf: RingBuffer<i32> = RingBuffer::with_mode(2, Mode::Fixed);
+
+        rf.push_back(0).unwrap();
+        rf.push_back(1).unwrap();
+        assert_eq!(Err(errordsa::Error::BufferFull), rf.push_back(2));
+        assert_eq!(2, rf.capacity());
+
+        assert_eq!(Some(0), rf.pop_front());
+        rf.push_back(2).unwrap();
+        assert_eq!(Some(1), rf.pop_front());
+        assert_eq!(Some(2), rf.pop_front());
+    }
+
+    #[test]
+    fn reserve_and_shrink() {
+        let mut rf: RingBuffer<i32> = RingBuffer::with_mode(4, Mode::Fixed);
+
+        rf.push_back(0).unwrap();
+        rf.push_back(1).unwrap();
+        rf.push_back(2).unwrap();
+        rf.push_back(3).unwrap();
+        rf.pop_front();
+        rf.push_back(4).unwrap();
+
+        rf.reserve(3);
+        assert_eq!(8, rf.capacity());
+        rf.reserve(4);
+        assert_eq!(8, rf.capacity());
+        rf.reserve(20);
+        assert_eq!(24, rf.capacity());
+        rf.push_back(5).unwrap();
+
+        rf.shrink_to_fit();
+        assert_eq!(5, rf.capacity());
+        assert_eq!(Err(errordsa::Error::BufferFull), rf.push_back(6));
+
+        for i in 1..6 {
+            assert_eq!(Some(i), rf.pop_front());
+        }
+        rf.shrink_to_fit();
+        assert_eq!(0, rf.capacity());
+        assert_eq!(None, rf.pop_front());
+    }
+
+    #[test]
+    fn overwrite_oldest() {
+        let mut rf: RingBuffer<i32> = RingBuffer::with_mode(3, Mode::Overwrite);
+
+        assert_eq!(Ok(None), rf.push_back(0));
+        assert_eq!(Ok(None), rf.push_back(1));
+        assert_eq!(Ok(None), rf.push_back(2));
+        assert_eq!(Ok(Some(0)), rf.push_back(3));
+        assert_eq!(Ok(Some(1)), rf.push_back(4));
+        assert_eq!(3, rf.capacity());
+
+        assert_eq!(vec![3, 4], rf.latest(2));
+        assert_eq!(vec![2, 3, 4], rf.latest(10));
+        assert_eq!(Vec::<i32>::new(), rf.latest(0));
+
+        assert_eq!(Some(2), rf.pop_front());
+        assert_eq!(Ok(None), rf.push_back(5));
+        assert_eq!(Ok(Some(3)), rf.push_back(6));
+        assert_eq!(vec![4, 5, 6], rf.latest(3));
+
+        let mut empty: RingBuffer<i32> = RingBuffer::with_mode(0, Mode::Overwrite);
+        assert_eq!(Ok(Some(7)), empty.push_back(7));
+        assert_eq!(Vec::<i32>::new(), empty.latest(1));
+    }
+
+    #[test]
+    fn double_ended() {
+        let mut rf: RingBuffer<i32> = RingBuffer::new(2);
+        assert!(rf.is_empty());
+        assert_eq!(None, rf.front());
+        assert_eq!(None, rf.back());
+
+        rf.push_back(2).unwrap();
+        rf.push_front(1).unwrap();
+        rf.push_front(0).unwrap();
+        rf.push_back(3).unwrap();
+
+        assert_eq!(4, rf.len());
+        assert_eq!(Some(&0), rf.front());
+        assert_eq!(Some(&3), rf.back());
+        assert_eq!(Some(3), rf.pop_back());
+        assert_eq!(Some(0), rf.pop_front());
+        assert_eq!(Some(2), rf.pop_back());
+        assert_eq!(Some(1), rf.pop_back());
+        assert_eq!(None, rf.pop_back());
+
+        let mut rf: RingBuffer<i32> = RingBuffer::with_mode(3, Mode::Overwrite);
+        rf.push_back(1).unwrap();
+        rf.push_back(2).unwrap();
+        rf.push_back(3).unwrap();
+        assert_eq!(Ok(Some(3)), rf.push_front(0));
+        assert_eq!(vec![0, 1, 2], rf.iter().copied().collect::<Vec<i32>>());
+
+        let mut rf: RingBuffer<i32> = RingBuffer::with_mode(1, Mode::Fixed);
+        rf.push_front(0).unwrap();
+        assert_eq!(Err(errordsa::Error::BufferFull), rf.push_front(1));
+    }
+
+    #[test]
+    fn indexing() {
+        let mut rf: RingBuffer<i32> = RingBuffer::new(4);
+        rf.push_back(1).unwrap();
+        rf.push_back(2).unwrap();
+        rf.push_front(0).unwrap();
+
+        assert_eq!(0, rf[0]);
+        assert_eq!(2, rf[2]);
+        assert_eq!(None, rf.get(3));
+
+        rf[1] = 10;
+        *rf.get_mut(2).unwrap() = 20;
+        *rf.front_mut().unwrap() -= 1;
+        assert_eq!(vec![-1, 10, 20], rf.iter().copied().collect::<Vec<i32>>());
+    }
+
+    #[test]
+    fn with_capacity() {
+        let mut rf: RingBuffer<i32> = RingBuffer::with_capacity(2);
+        assert_eq!(2, rf.capacity());
+        assert_eq!(Mode::Growable, rf.mode());
+        assert!(rf.is_empty());
+
+        for i in 0..3 {
+            rf.push_back(i).unwrap();
+        }
+        assert_eq!(4, rf.capacity());
+        assert_eq!(vec![0, 1, 2], rf.iter().copied().collect::<Vec<i32>>());
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_out_of_bounds() {
+        let mut rf: RingBuffer<i32> = RingBuffer::new(4);
+        rf.push_back(1).unwrap();
+        let _ = rf[1];
+    }
+
+    #[test]
+    fn iterators_and_slices() {
+        let mut rf: RingBuffer<i32> = RingBuffer::with_mode(4, Mode::Fixed);
+        rf.push_back(2).unwrap();
+        rf.push_back(3).unwrap();
+        rf.push_front(1).unwrap();
+        rf.push_front(0).unwrap();
+
+        let (a, b) = rf.as_slices();
+        assert_eq!((&[0, 1][..], &[2, 3][..]), (a, b));
+        assert_eq!(vec![3, 2, 1, 0], rf.iter().rev().copied().collect::<Vec<i32>>());
+
+        for value in rf.iter_mut() {
+            *value *= 10;
+        }
+        assert_eq!(vec![0, 10, 20, 30], (&rf).into_iter().copied().collect::<Vec<i32>>());
+
+        // the values are moved within the same buffer
+        let buffer: *const MaybeUninit<i32> = rf.buffer.as_ptr();
+        assert_eq!(&mut [0, 10, 20, 30], rf.make_contiguous());
+        assert_eq!((&[0, 10, 20, 30][..], &[][..]), rf.as_slices());
+        assert_eq!(4, rf.capacity());
+        assert_eq!(buffer, rf.buffer.as_ptr());
+
+        // with free slots between the back and the front
+        let mut rf: RingBuffer<i32> = RingBuffer::new(6);
+        for i in 0..6 {
+            rf.push_back(i).unwrap();
+        }
+        for _ in 0..4 {
+            rf.pop_front();
+        }
+        rf.push_back(6).unwrap();
+        rf.push_back(7).unwrap();
+        let buffer: *const MaybeUninit<i32> = rf.buffer.as_ptr();
+        assert_eq!(&mut [4, 5, 6, 7], rf.make_contiguous());
+        assert_eq!(buffer, rf.buffer.as_ptr());
+        rf.push_back(8).unwrap();
+        assert_eq!(vec![4, 5, 6, 7, 8], rf.iter().copied().collect::<Vec<i32>>());
+
+        rf.clear();
+        assert!(rf.is_empty());
+        assert_eq!((&[][..], &[][..]), rf.as_slices());
+    }
+
+    #[test]
+    fn rotate() {
+        let mut rf: RingBuffer<i32> = RingBuffer::new(8);
+        for i in 0..5 {
+            rf.push_back(i).unwrap();
+        }
+
+        rf.rotate_left(2);
+        assert_eq!(vec![2, 3, 4, 0, 1], rf.iter().copied().collect::<Vec<i32>>());
+        rf.rotate_left(4);
+        assert_eq!(vec![1, 2, 3, 4, 0], rf.iter().copied().collect::<Vec<i32>>());
+        rf.rotate_right(1);
+        assert_eq!(vec![0, 1, 2, 3, 4], rf.iter().copied().collect::<Vec<i32>>());
+        rf.rotate_right(5);
+        assert_eq!(vec![0, 1, 2, 3, 4], rf.iter().copied().collect::<Vec<i32>>());
+
+        // a full buffer rotates by just moving its start
+        let mut rf: RingBuffer<i32> = RingBuffer::with_mode(3, Mode::Fixed);
+        for i in 0..3 {
+            rf.push_back(i).unwrap();
+        }
+        rf.rotate_right(1);
+        assert_eq!(vec![2, 0, 1], rf.iter().copied().collect::<Vec<i32>>());
+    }
+
+    #[test]
+    fn non_copy_values() {
+        let mut rf: RingBuffer<String> = RingBuffer::new(2);
+        rf.push_back("b".to_owned()).unwrap();
+        rf.push_front("a".to_owned()).unwrap();
+        rf.push_back("c".to_owned()).unwrap();
+
+        assert_eq!("a", rf[0]);
+        rf[2].push('!');
+        assert_eq!(vec!["b".to_owned(), "c!".to_owned()], rf.latest(2));
+        assert_eq!(Some("a".to_owned()), rf.pop_front());
+        assert_eq!(vec!["c!".to_owned(), "b".to_owned()], rf.into_iter().rev().collect::<Vec<String>>());
+    }
+
+    #[test]
+    fn drops_popped_and_remaining_values() {
+        let drops: Rc<Cell<usize>> = Rc::new(Cell::new(0));
+        let mut rf: RingBuffer<DropCounter> = RingBuffer::new(2);
+        for _ in 0..5 {
+            rf.push_back(DropCounter { drops: drops.clone() }).unwrap();
+        }
+        assert_eq!(0, drops.get());
+
+        drop(rf.pop_front());
+        drop(rf.pop_back());
+        assert_eq!(2, drops.get());
+
+        // moving the values around must not drop them
+        rf.rotate_left(1);
+        rf.make_contiguous();
+        rf.shrink_to_fit();
+        assert_eq!(2, drops.get());
+
+        drop(rf);
+        assert_eq!(5, drops.get());
+    }
+
+    #[test]
+    fn drops_on_clear_and_overwrite() {
+        let drops: Rc<Cell<usize>> = Rc::new(Cell::new(0));
+        let mut rf: RingBuffer<DropCounter> = RingBuffer::with_mode(3, Mode::Overwrite);
+        for _ in 0..3 {
+            rf.push_back(DropCounter { drops: drops.clone() }).unwrap();
+        }
+
+        let evicted: Option<DropCounter> = rf.push_front(DropCounter { drops: drops.clone() }).unwrap();
+        assert_eq!(0, drops.get());
+        drop(evicted);
+        assert_eq!(1, drops.get());
+
+        // wrapped around, so both slices have to be dropped
+        rf.push_back(DropCounter { drops: drops.clone() }).unwrap().unwrap();
+        assert_eq!(2, drops.get());
+        rf.clear();
+        assert_eq!(5, drops.get());
+        assert!(rf.is_empty());
+
+        rf.push_back(DropCounter { drops: drops.clone() }).unwrap();
+        let mut iter: IntoIter<DropCounter> = rf.into_iter();
+        rf = RingBuffer::new(0);
+        assert_eq!(5, drops.get());
+        drop(iter.next());
+        assert_eq!(6, drops.get());
+        assert!(rf.is_empty());
+    }
+
+    #[test]
+    fn zero_sized_values() {
+        let mut rf: RingBuffer<()> = RingBuffer::new(0);
+        for _ in 0..10 {
+            rf.push_back(()).unwrap();
+        }
+        rf.rotate_right(3);
+        assert_eq!(10, rf.len());
+        assert_eq!(10, rf.iter().count());
+        assert_eq!(Some(()), rf.pop_back());
+    }
 }
@@ -0,0 +1,307 @@
+#![allow(dead_code)]
+
+use std::{ cell::Cell, time::Instant };
+
+use crate::{ errordsa, heap::MinHeap };
+
+// Runs tasks (any payload) once their deadline comes. Deadlines are u64 ticks of a Clock, which is a trait so a
+// simulation (or a test) can use a ManualClock and move the time by hand, while SystemClock counts real milliseconds.
+// The tasks are kept in a MinHeap keyed by deadline, and removing something from the middle of a heap is expensive,
+// so cancel and reschedule use lazy deletion: the task is marked and its old heap entry stays until it reaches the top,
+// where it's recognized as stale and dropped. Every entry carries the version of its task, which reschedule bumps.
+// Stale entries of far-off deadlines (like a timeout pushed back again and again) would never reach the top, so the
+// heap is rebuilt without them once they outnumber the live tasks, which keeps it under twice their amount.
+
+pub trait Clock {
+    fn now(&self) -> u64;
+}
+
+pub struct ManualClock {
+    now: Cell<u64>,
+}
+
+impl ManualClock {
+    pub fn new(now: u64) -> ManualClock {
+        ManualClock { now: Cell::new(now) }
+    }
+
+    pub fn set(&self, now: u64) {
+        self.now.set(now);
+    }
+
+    pub fn advance(&self, ticks: u64) {
+        self.now.set(self.now.get() + ticks);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> u64 {
+        self.now.get()
+    }
+}
+
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> SystemClock {
+        SystemClock { start: Instant::now() }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        // milliseconds since the clock was created
+        self.start.elapsed().as_millis() as u64
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct TaskHandle {
+    index: usize,
+    generation: usize,
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct Entry {
+    deadline: u64,
+    seq: u64, // tasks with the same deadline run in the order they were scheduled
+    index: usize,
+    version: usize,
+}
+
+struct Task<P> {
+    payload: Option<P>,
+    deadline: u64,
+    generation: usize, // changes when the slot is freed, so old handles stop working
+    version: usize, // changes when the task is rescheduled, so its old entries are stale
+}
+
+pub struct Scheduler<P, C: Clock> {
+    clock: C,
+    queue: MinHeap<Entry>,
+    tasks: Vec<Task<P>>,
+    free: Vec<usize>,
+    seq: u64,
+    len: usize,
+}
+
+impl<P, C: Clock> Scheduler<P, C> {
+    pub fn new(clock: C) -> Scheduler<P, C> {
+        Scheduler {
+            clock,
+            queue: MinHeap::new(),
+            tasks: Vec::<Task<P>>::new(),
+            free: Vec::<usize>::new(),
+            seq: 0,
+            len: 0,
+        }
+    }
+
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    pub fn len(&self) -> usize {
+        // amount of pending tasks, not counting the cancelled ones
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn schedule(&mut self, at: u64, payload: P) -> TaskHandle {
+        let index: usize = match self.free.pop() {
+            Some(index) => index,
+            None => {
+                self.tasks.push(Task { payload: None, deadline: 0, generation: 0, version: 0 });
+                self.tasks.len() - 1
+            }
+        };
+        self.tasks[index].payload = Some(payload);
+        self.len += 1;
+        self.enqueue(index, at);
+        TaskHandle { index, generation: self.tasks[index].generation }
+    }
+
+    pub fn schedule_after(&mut self, delay: u64, payload: P) -> TaskHandle {
+        let at: u64 = self.clock.now() + delay;
+        self.schedule(at, payload)
+    }
+
+    pub fn deadline(&self, handle: TaskHandle) -> Option<u64> {
+        Some(self.tasks[self.resolve(handle)?].deadline)
+    }
+
+    pub fn cancel(&mut self, handle: TaskHandle) -> Option<P> {
+        // returns the payload, or None if the task already ran or was cancelled
+        let index: usize = self.resolve(handle)?;
+        let payload: P = self.free_task(index);
+        self.compact();
+        Some(payload)
+    }
+
+    pub fn reschedule(&mut self, handle: TaskHandle, at: u64) -> Result<(), errordsa::Error> {
+        let index: usize = self.resolve(handle).ok_or(errordsa::Error::InvalidHandle)?;
+        self.tasks[index].version += 1;
+        self.enqueue(index, at);
+        self.compact();
+        Ok(())
+    }
+
+    pub fn next_deadline(&mut self) -> Option<u64> {
+        self.drop_stale();
+        Some(self.queue.peek()?.deadline)
+    }
+
+    pub fn pop_due(&mut self, now: u64) -> Vec<(u64, P)> {
+        // all the tasks whose deadline is now or earlier, with their deadlines, from the earliest one
+        let mut due: Vec<(u64, P)> = vec![];
+        loop {
+            self.drop_stale();
+            match self.queue.peek() {
+                Some(entry) if entry.deadline <= now => {
+                    let entry: Entry = self.queue.pop().unwrap();
+                    due.push((entry.deadline, self.free_task(entry.index)));
+                }
+                _ => return due,
+            }
+        }
+    }
+
+    pub fn pop_expired(&mut self) -> Vec<(u64, P)> {
+        // pop_due with the current time of the clock
+        self.pop_due(self.clock.now())
+    }
+
+    fn resolve(&self, handle: TaskHandle) -> Option<usize> {
+        let task: &Task<P> = self.tasks.get(handle.index)?;
+        if task.generation != handle.generation || task.payload.is_none() {
+            return None;
+        }
+        Some(handle.index)
+    }
+
+    fn enqueue(&mut self, index: usize, at: u64) {
+        self.tasks[index].deadline = at;
+        self.queue.insert(Entry { deadline: at, seq: self.seq, index, version: self.tasks[index].version });
+        self.seq += 1;
+    }
+
+    fn free_task(&mut self, index: usize) -> P {
+        let task: &mut Task<P> = &mut self.tasks[index];
+        task.generation += 1;
+        task.version += 1; // its entry in the queue (if any) becomes stale
+        self.free.push(index);
+        self.len -= 1;
+        task.payload.take().unwrap()
+    }
+
+    fn compact(&mut self) {
+        // every live task has exactly one entry, so the rest are stale
+        if self.queue.len() - self.len <= self.len {
+            return;
+        }
+        let tasks: &Vec<Task<P>> = &self.tasks;
+        let live: Vec<Entry> = self.queue.drain().filter(|entry| tasks[entry.index].version == entry.version).collect();
+        self.queue = MinHeap::from_vec(live);
+    }
+
+    fn drop_stale(&mut self) {
+        // pops the entries of cancelled or rescheduled tasks from the top of the queue
+        while let Some(entry) = self.queue.peek() {
+            if self.tasks[entry.index].version == entry.version {
+                return;
+            }
+            self.queue.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scheduler_pop_due() {
+        let mut scheduler: Scheduler<&str, ManualClock> = Scheduler::new(ManualClock::new(0));
+        scheduler.schedule(30, "c");
+        scheduler.schedule(10, "a");
+        scheduler.schedule_after(20, "b");
+        scheduler.schedule(10, "a2");
+        assert_eq!(4, scheduler.len());
+        assert_eq!(Some(10), scheduler.next_deadline());
+
+        assert_eq!(Vec::<(u64, &str)>::new(), scheduler.pop_expired());
+        scheduler.clock().advance(20);
+        assert_eq!(vec![(10, "a"), (10, "a2"), (20, "b")], scheduler.pop_expired());
+        assert_eq!(vec![(30, "c")], scheduler.pop_due(100));
+        assert!(scheduler.is_empty());
+        assert_eq!(None, scheduler.next_deadline());
+    }
+
+    #[test]
+    fn scheduler_cancel_and_reschedule() {
+        let mut scheduler: Scheduler<u32, ManualClock> = Scheduler::new(ManualClock::new(0));
+        let a: TaskHandle = scheduler.schedule(10, 1);
+        let b: TaskHandle = scheduler.schedule(20, 2);
+        let c: TaskHandle = scheduler.schedule(30, 3);
+
+        assert_eq!(Some(1), scheduler.cancel(a));
+        assert_eq!(None, scheduler.cancel(a));
+        scheduler.reschedule(c, 5).unwrap();
+        scheduler.reschedule(b, 40).unwrap();
+        assert_eq!(Some(40), scheduler.deadline(b));
+        assert_eq!(2, scheduler.len());
+
+        // the slot of a is reused, but its handle doesn't point to the new task
+        let d: TaskHandle = scheduler.schedule(25, 4);
+        assert_eq!(None, scheduler.deadline(a));
+        assert_eq!(Err(errordsa::Error::InvalidHandle), scheduler.reschedule(a, 1));
+
+        assert_eq!(Some(5), scheduler.next_deadline());
+        assert_eq!(vec![(5, 3), (25, 4)], scheduler.pop_due(30));
+        assert_eq!(None, scheduler.cancel(d));
+        assert_eq!(None, scheduler.cancel(c));
+        assert_eq!(vec![(40, 2)], scheduler.pop_due(40));
+        assert!(scheduler.queue.is_empty());
+    }
+
+    #[test]
+    fn scheduler_retries() {
+        // a task that fails is retried with exponential backoff, until it succeeds at the 4th attempt
+        let clock: ManualClock = ManualClock::new(100);
+        let mut scheduler: Scheduler<u32, ManualClock> = Scheduler::new(clock);
+        scheduler.schedule_after(0, 1);
+        let mut runs: Vec<(u64, u32)> = vec![];
+        while let Some(deadline) = scheduler.next_deadline() {
+            scheduler.clock().set(deadline);
+            for (at, attempt) in scheduler.pop_expired() {
+                runs.push((at, attempt));
+                if attempt < 4 {
+                    scheduler.schedule_after(10 << attempt, attempt + 1);
+                }
+            }
+        }
+        assert_eq!(vec![(100, 1), (120, 2), (160, 3), (240, 4)], runs);
+    }
+
+    #[test]
+    fn scheduler_compacts_stale_entries() {
+        // a timeout pushed back over and over, next to tasks that are cancelled
+        let mut scheduler: Scheduler<u32, ManualClock> = Scheduler::new(ManualClock::new(0));
+        let timeout: TaskHandle = scheduler.schedule(1000, 0);
+        for i in 0..10_000 {
+            scheduler.reschedule(timeout, 1000 + i).unwrap();
+            let task: TaskHandle = scheduler.schedule(5000 + i, 1);
+            scheduler.cancel(task);
+            assert!(scheduler.queue.len() <= 2 * scheduler.len() + 1);
+        }
+        assert_eq!(1, scheduler.len());
+        assert_eq!(Some(10_999), scheduler.next_deadline());
+        assert_eq!(vec![(10_999, 0)], scheduler.pop_due(20_000));
+        assert!(scheduler.queue.is_empty());
+    }
+}
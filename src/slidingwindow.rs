@@ -0,0 +1,227 @@
+#![allow(dead_code)]
+
+use crate::ringbuffer::{ Mode, RingBuffer };
+
+// Statistics over the last `size` values pushed, all updated in O(1) amortized per push.
+// The values live in an overwriting RingBuffer, so every push past the size evicts the oldest value, and the
+// aggregates are updated with both the new and the evicted value instead of being recomputed.
+// The mean and the variance use Welford's method (which is numerically stable, unlike keeping a sum of
+// squares), extended so that values can also be removed.
+// For the min and max there are two monotonic deques: the min one is increasing from front to back, since a value
+// that is bigger than a newer one can never be the minimum again (the newer one leaves the window later).
+// So the front is always the minimum, and each value is pushed and popped at most once.
+
+pub struct SlidingWindow {
+    values: RingBuffer<f64>,
+    pushed: u64, // how many values were ever pushed, used to know when a value leaves the window
+    sum: f64,
+    mean: f64,
+    m2: f64, // sum of the squared differences from the mean
+    mins: RingBuffer<(u64, f64)>,
+    maxs: RingBuffer<(u64, f64)>,
+}
+
+impl SlidingWindow {
+    pub fn new(size: usize) -> SlidingWindow {
+        assert!(size > 0, "a sliding window needs room for at least one value");
+        SlidingWindow {
+            values: RingBuffer::with_mode(size, Mode::Overwrite),
+            pushed: 0,
+            sum: 0.0,
+            mean: 0.0,
+            m2: 0.0,
+            mins: RingBuffer::new(size),
+            maxs: RingBuffer::new(size),
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.values.capacity()
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn push(&mut self, value: f64) {
+        // when the window is full the new value takes the place of the evicted one, so len() doesn't change
+        if let Ok(Some(evicted)) = self.values.push_back(value) {
+            self.remove(evicted, self.len() - 1);
+        }
+        self.add(value, self.len());
+
+        let index: u64 = self.pushed;
+        self.pushed += 1;
+        let oldest: u64 = self.pushed - (self.len() as u64);
+        SlidingWindow::push_monotonic(&mut self.mins, index, value, oldest, |back, new| back >= new);
+        SlidingWindow::push_monotonic(&mut self.maxs, index, value, oldest, |back, new| back <= new);
+    }
+
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        if self.is_empty() {
+            return None;
+        }
+        Some(self.mean)
+    }
+
+    pub fn variance(&self) -> Option<f64> {
+        // population variance of the values in the window
+        if self.is_empty() {
+            return None;
+        }
+        Some(f64::max(self.m2, 0.0) / (self.len() as f64))
+    }
+
+    pub fn std_dev(&self) -> Option<f64> {
+        self.variance().map(f64::sqrt)
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        self.mins.front().map(|(_, value)| *value)
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        self.maxs.front().map(|(_, value)| *value)
+    }
+
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.mins.clear();
+        self.maxs.clear();
+        self.sum = 0.0;
+        self.mean = 0.0;
+        self.m2 = 0.0;
+    }
+
+    fn add(&mut self, value: f64, count: usize) {
+        // count is the amount of values in the window, including the added one
+        let n: f64 = count as f64;
+        let delta: f64 = value - self.mean;
+        self.sum += value;
+        self.mean += delta / n;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    fn remove(&mut self, value: f64, count: usize) {
+        // count is the amount of values left in the window after the removal
+        let n: f64 = count as f64;
+        self.sum -= value;
+        if n == 0.0 {
+            self.mean = 0.0;
+            self.m2 = 0.0;
+            return;
+        }
+        let delta: f64 = value - self.mean;
+        self.mean -= delta / n;
+        self.m2 -= delta * (value - self.mean);
+    }
+
+    fn push_monotonic<F: Fn(f64, f64) -> bool>(
+        deque: &mut RingBuffer<(u64, f64)>,
+        index: u64,
+        value: f64,
+        oldest: u64,
+        dominated: F
+    ) {
+        // first the values that left the window are dropped from the front, then the ones that
+        // can no longer be the answer once `value` is in (`dominated(back, value)`) from the back
+        while let Some((front, _)) = deque.front() {
+            if *front >= oldest {
+                break;
+            }
+            deque.pop_front();
+        }
+        while let Some((_, back)) = deque.back() {
+            if !dominated(*back, value) {
+                break;
+            }
+            deque.pop_back();
+        }
+        deque.push_back((index, value)).ok(); // the deque is growable, so this can't fail
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::next_random;
+
+    fn assert_close(expected: f64, actual: f64) {
+        assert!((expected - actual).abs() < 1e-9, "expected {}, got {}", expected, actual);
+    }
+
+    #[test]
+    fn window_statistics() {
+        let mut window: SlidingWindow = SlidingWindow::new(3);
+        assert_eq!(None, window.mean());
+        assert_eq!(None, window.min());
+        assert_eq!(0.0, window.sum());
+
+        window.push(2.0);
+        window.push(4.0);
+        assert_close(6.0, window.sum());
+        assert_close(3.0, window.mean().unwrap());
+        assert_close(1.0, window.variance().unwrap());
+        assert_eq!(Some(2.0), window.min());
+        assert_eq!(Some(4.0), window.max());
+
+        window.push(9.0);
+        window.push(1.0); // 2 leaves the window
+        assert_eq!(3, window.len());
+        assert_close(14.0, window.sum());
+        assert_close(14.0 / 3.0, window.mean().unwrap());
+        assert_eq!(Some(1.0), window.min());
+        assert_eq!(Some(9.0), window.max());
+
+        window.push(3.0);
+        window.push(3.0); // 9 leaves the window
+        assert_eq!(Some(1.0), window.min());
+        assert_eq!(Some(3.0), window.max());
+        window.push(5.0); // 1 leaves the window
+        assert_eq!(Some(3.0), window.min());
+        assert_eq!(Some(5.0), window.max());
+
+        window.clear();
+        assert!(window.is_empty());
+        assert_eq!(None, window.variance());
+        window.push(7.0);
+        assert_eq!(Some(7.0), window.min());
+        assert_close(0.0, window.std_dev().unwrap());
+    }
+
+    #[test]
+    fn matches_recomputing() {
+        let size: usize = 7;
+        let mut window: SlidingWindow = SlidingWindow::new(size);
+        let mut values: Vec<f64> = vec![];
+        let mut seed: u64 = 42;
+
+        for _ in 0..500 {
+            let value: f64 = (next_random(&mut seed) % 1000) as f64 / 10.0 - 50.0;
+            window.push(value);
+            values.push(value);
+
+            let last: &[f64] = &values[values.len().saturating_sub(size)..];
+            let n: f64 = last.len() as f64;
+            let mean: f64 = last.iter().sum::<f64>() / n;
+            let variance: f64 = last
+                .iter()
+                .map(|v| (v - mean) * (v - mean))
+                .sum::<f64>() / n;
+
+            assert_close(last.iter().sum(), window.sum());
+            assert_close(mean, window.mean().unwrap());
+            assert_close(variance, window.variance().unwrap());
+            assert_eq!(last.iter().copied().reduce(f64::min), window.min());
+            assert_eq!(last.iter().copied().reduce(f64::max), window.max());
+        }
+    }
+}
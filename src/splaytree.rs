@@ -0,0 +1,368 @@
+#![allow(dead_code)]
+
+use std::cmp::Ordering;
+
+use crate::orderedset::{ self, OrderedSet, SearchNode };
+
+// A splay tree is a binary search tree that moves every value it accesses to the root, with rotations that also
+// roughly halve the depth of the nodes on the way (splaying). It keeps no balance information, and a single operation
+// can take O(n), but any sequence of operations takes amortized O(log n) each. Values that are accessed often stay
+// near the root, so repeated accesses to the same few values are very fast.
+// Splaying is done top-down, in a single loop, since the search paths can be as long as the tree. A value that isn't
+// in the tree brings the last node of its search path to the root instead. All the queries splay, including floor
+// and the like, which is why OrderedSet takes &mut self for them.
+
+struct Node<T> {
+    value: T,
+    left: Option<Box<Node<T>>>,
+    right: Option<Box<Node<T>>>,
+}
+
+impl<T: PartialOrd> Node<T> {
+    fn new(value: T) -> Node<T> {
+        Node {
+            value,
+            left: None,
+            right: None,
+        }
+    }
+
+    fn splay<F: Fn(&T) -> Ordering>(mut root: Box<Node<T>>, direction: &F) -> Box<Node<T>> {
+        // top-down: on the way down, the nodes smaller than the target are taken out to a left tree and the bigger
+        // ones to a right tree, and at the end both trees become the children of the last node of the path.
+        // direction says on which side of a node the target is, Equal when it's the node itself
+        let mut smaller: Vec<Box<Node<T>>> = vec![]; // the left tree, each node hangs from the right of the previous
+        let mut bigger: Vec<Box<Node<T>>> = vec![]; // the right tree, each node hangs from the left of the previous
+        loop {
+            match direction(&root.value) {
+                Ordering::Less => {
+                    let Some(mut left) = root.left.take() else {
+                        break;
+                    };
+                    if direction(&left.value) == Ordering::Less {
+                        // zig-zig: rotate right before going down
+                        root.left = left.right.take();
+                        left.right = Some(root);
+                        root = left;
+                        match root.left.take() {
+                            None => break,
+                            Some(next) => left = next,
+                        }
+                    }
+                    bigger.push(root);
+                    root = left;
+                }
+                Ordering::Greater => {
+                    let Some(mut right) = root.right.take() else {
+                        break;
+                    };
+                    if direction(&right.value) == Ordering::Greater {
+                        // zig-zig: rotate left before going down
+                        root.right = right.left.take();
+                        right.left = Some(root);
+                        root = right;
+                        match root.right.take() {
+                            None => break,
+                            Some(next) => right = next,
+                        }
+                    }
+                    smaller.push(root);
+                    root = right;
+                }
+                Ordering::Equal => break,
+            }
+        }
+        for mut node in smaller.into_iter().rev() {
+            node.right = root.left.take();
+            root.left = Some(node);
+        }
+        for mut node in bigger.into_iter().rev() {
+            node.left = root.right.take();
+            root.right = Some(node);
+        }
+        root
+    }
+}
+
+impl<T> SearchNode<T> for Node<T> {
+    fn value(&self) -> &T {
+        &self.value
+    }
+
+    fn left(&self) -> Option<&Node<T>> {
+        self.left.as_deref()
+    }
+
+    fn right(&self) -> Option<&Node<T>> {
+        self.right.as_deref()
+    }
+}
+
+pub struct SplayTree<T> {
+    root: Option<Box<Node<T>>>,
+    len: usize,
+}
+
+impl<T: PartialOrd> SplayTree<T> {
+    pub fn new() -> SplayTree<T> {
+        SplayTree {
+            root: None,
+            len: 0,
+        }
+    }
+
+    pub fn root(&self) -> Option<&T> {
+        // the last value accessed
+        self.root.as_ref().map(|root| &root.value)
+    }
+
+    fn splay(&mut self, value: &T) {
+        self.splay_by(&|v: &T| compare(value, v));
+    }
+
+    fn splay_by<F: Fn(&T) -> Ordering>(&mut self, direction: &F) {
+        if let Some(root) = self.root.take() {
+            self.root = Some(Node::splay(root, direction));
+        }
+    }
+
+    fn first_where<F: Fn(&T) -> bool>(&mut self, is_after: F) -> Option<&T> {
+        // like orderedset::first_where, but the value found ends up at the root
+        self.splay_by(&|v: &T| if is_after(v) { Ordering::Less } else { Ordering::Greater });
+        let mut root: Box<Node<T>> = self.root.take()?;
+        if !is_after(&root.value) {
+            // the search ended at the last value before, so the one wanted is the smallest one of its right subtree
+            if let Some(right) = root.right.take() {
+                let mut right: Box<Node<T>> = Node::splay(right, &|_: &T| Ordering::Less);
+                right.left = Some(root);
+                root = right;
+            }
+        }
+        self.root = Some(root);
+        self.root.as_ref().map(|root| &root.value).filter(|value| is_after(value))
+    }
+
+    fn last_where<F: Fn(&T) -> bool>(&mut self, is_before: F) -> Option<&T> {
+        self.splay_by(&|v: &T| if is_before(v) { Ordering::Greater } else { Ordering::Less });
+        let mut root: Box<Node<T>> = self.root.take()?;
+        if !is_before(&root.value) {
+            if let Some(left) = root.left.take() {
+                let mut left: Box<Node<T>> = Node::splay(left, &|_: &T| Ordering::Greater);
+                left.right = Some(root);
+                root = left;
+            }
+        }
+        self.root = Some(root);
+        self.root.as_ref().map(|root| &root.value).filter(|value| is_before(value))
+    }
+}
+
+fn compare<T: PartialOrd>(target: &T, value: &T) -> Ordering {
+    // on which side of value the target is
+    if target < value {
+        Ordering::Less
+    } else if target > value {
+        Ordering::Greater
+    } else {
+        Ordering::Equal
+    }
+}
+
+impl<T: PartialOrd> OrderedSet<T> for SplayTree<T> {
+    fn insert(&mut self, value: T) -> bool {
+        self.splay(&value);
+        let mut node: Box<Node<T>> = Box::new(Node::new(value));
+        match self.root.take() {
+            None => {}
+            Some(mut root) => {
+                // the root is the value right before or right after the new one, so the tree splits around it
+                if node.value < root.value {
+                    node.left = root.left.take();
+                    node.right = Some(root);
+                } else if node.value > root.value {
+                    node.right = root.right.take();
+                    node.left = Some(root);
+                } else {
+                    self.root = Some(root);
+                    return false;
+                }
+            }
+        }
+        self.root = Some(node);
+        self.len += 1;
+        true
+    }
+
+    fn remove(&mut self, value: &T) -> bool {
+        if !self.contains(value) {
+            return false;
+        }
+        let mut root: Box<Node<T>> = self.root.take().unwrap();
+        self.root = match root.left.take() {
+            None => root.right.take(),
+            Some(left) => {
+                // the biggest value of the left subtree comes to the top, so it has no right child
+                let mut left: Box<Node<T>> = Node::splay(left, &|_: &T| Ordering::Greater);
+                left.right = root.right.take();
+                Some(left)
+            }
+        };
+        self.len -= 1;
+        true
+    }
+
+    fn contains(&mut self, value: &T) -> bool {
+        self.splay(value);
+        self.root.as_ref().is_some_and(|root| root.value == *value)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn min(&mut self) -> Option<&T> {
+        self.first_where(|_| true)
+    }
+
+    fn max(&mut self) -> Option<&T> {
+        self.last_where(|_| true)
+    }
+
+    fn floor(&mut self, value: &T) -> Option<&T> {
+        self.last_where(|v| v <= value)
+    }
+
+    fn ceiling(&mut self, value: &T) -> Option<&T> {
+        self.first_where(|v| v >= value)
+    }
+
+    fn predecessor(&mut self, value: &T) -> Option<&T> {
+        self.last_where(|v| v < value)
+    }
+
+    fn successor(&mut self, value: &T) -> Option<&T> {
+        self.first_where(|v| v > value)
+    }
+
+    fn in_order_traverse<F: FnMut(&T)>(&self, visit_func: &mut F) {
+        orderedset::in_order_traverse(self.root.as_deref(), visit_func);
+    }
+}
+
+impl<T> Drop for SplayTree<T> {
+    fn drop(&mut self) {
+        // the default drop is recursive, and the tree can be a single long path
+        let mut stack: Vec<Box<Node<T>>> = self.root.take().into_iter().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.left.take());
+            stack.extend(node.right.take());
+        }
+    }
+}
+
+impl<T: PartialOrd> Default for SplayTree<T> {
+    fn default() -> SplayTree<T> {
+        SplayTree::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn depth(tree: &SplayTree<i32>, value: i32) -> usize {
+        let mut depth: usize = 0;
+        let mut node: &Node<i32> = tree.root.as_ref().unwrap();
+        while value != node.value {
+            node = if value < node.value { node.left.as_ref() } else { node.right.as_ref() }.unwrap();
+            depth += 1;
+        }
+        depth
+    }
+
+    fn height(tree: &SplayTree<i32>) -> usize {
+        let mut height: usize = 0;
+        let mut stack: Vec<(&Node<i32>, usize)> = tree.root.as_deref().map(|root| (root, 1)).into_iter().collect();
+        while let Some((node, depth)) = stack.pop() {
+            height = height.max(depth);
+            stack.extend(node.left.as_deref().map(|left| (left, depth + 1)));
+            stack.extend(node.right.as_deref().map(|right| (right, depth + 1)));
+        }
+        height
+    }
+
+    #[test]
+    fn splay_tree_moves_accesses_to_the_root() {
+        let mut tree: SplayTree<i32> = SplayTree::new();
+        for value in 0..1000 {
+            tree.insert(value);
+        }
+        // sorted insertions leave a path, with the last value at the root
+        assert_eq!(Some(&999), tree.root());
+        assert_eq!(999, depth(&tree, 0));
+
+        // accessing the deepest value brings it to the root and halves the depth of the path
+        assert!(tree.contains(&0));
+        assert_eq!(Some(&0), tree.root());
+        assert!(depth(&tree, 999) < 510);
+
+        // a missing value brings one of its neighbours
+        assert!(!tree.contains(&1500));
+        assert_eq!(Some(&999), tree.root());
+
+        assert!(tree.remove(&500));
+        assert!(!tree.contains(&500));
+        assert_eq!(999, tree.len());
+        assert_eq!(Some(&499), tree.predecessor(&500));
+        assert_eq!(Some(&501), tree.successor(&500));
+    }
+
+    #[test]
+    fn splay_tree_queries_splay() {
+        let mut tree: SplayTree<i32> = SplayTree::new();
+        for value in (0..100).map(|v| v * 10) {
+            tree.insert(value);
+        }
+        // the answer of every query ends up at the root
+        assert_eq!(Some(&0), tree.min());
+        assert_eq!(Some(&0), tree.root());
+        assert_eq!(Some(&990), tree.max());
+        assert_eq!(Some(&990), tree.root());
+        assert_eq!(Some(&500), tree.floor(&505));
+        assert_eq!(Some(&500), tree.root());
+        assert_eq!(Some(&510), tree.ceiling(&505));
+        assert_eq!(Some(&510), tree.root());
+        assert_eq!(Some(&300), tree.predecessor(&310));
+        assert_eq!(Some(&300), tree.root());
+        assert_eq!(Some(&320), tree.successor(&310));
+        assert_eq!(Some(&320), tree.root());
+        assert_eq!(None, tree.floor(&-1));
+        assert_eq!(None, tree.successor(&990));
+        assert_eq!(100, tree.len());
+    }
+
+    #[test]
+    fn splay_tree_long_paths() {
+        // sorted insertions leave a path as long as the tree, which mustn't overflow the stack when splaying,
+        // traversing or dropping it
+        let mut tree: SplayTree<i32> = SplayTree::new();
+        for value in 0..50_000 {
+            tree.insert(value);
+        }
+        assert!(tree.contains(&0));
+        assert_eq!(Some(&49_999), tree.max());
+        let mut count: usize = 0;
+        tree.in_order_traverse(&mut (|_: &i32| count += 1));
+        assert_eq!(50_000, count);
+
+        // a query at the bottom of the path brings the value to the root and halves the height of the tree
+        let mut degenerate: SplayTree<i32> = SplayTree::new();
+        for value in 0..50_000 {
+            degenerate.insert(value);
+        }
+        assert_eq!(50_000, height(&degenerate));
+        assert_eq!(Some(&0), degenerate.min());
+        assert_eq!(0, depth(&degenerate, 0));
+        assert_eq!(25_001, height(&degenerate));
+    }
+}
@@ -0,0 +1,370 @@
+#![allow(dead_code)]
+
+use std::{
+    cell::UnsafeCell,
+    mem::MaybeUninit,
+    ops::{ Deref, DerefMut },
+    ptr,
+    sync::{ atomic::{ AtomicUsize, Ordering }, Arc },
+};
+
+use crate::ringbuffer::RingBuffer;
+
+// Single-producer single-consumer ring buffer, split in two halves that can live in different threads.
+// Only the producer moves the tail and only the consumer moves the head, so no locks are needed: each side
+// publishes its index with a Release store and reads the other's with an Acquire load, which makes the slots
+// written (or emptied) before the store visible to the other thread.
+// head and tail count elements since the creation of the buffer (wrapping on overflow), and the capacity is a
+// power of two so that the slot of an index is just `index & mask`.
+// head and tail are written by different threads, so each one gets a cache line of its own: if they shared one,
+// every push and pop would take the line away from the other core (false sharing).
+
+struct Shared<T> {
+    buffer: Box<[UnsafeCell<MaybeUninit<T>>]>,
+    mask: usize,
+    head: Padded, // next slot to read
+    tail: Padded, // next slot to write
+}
+
+#[repr(align(64))]
+struct Padded(AtomicUsize);
+
+impl Deref for Padded {
+    type Target = AtomicUsize;
+
+    fn deref(&self) -> &AtomicUsize {
+        &self.0
+    }
+}
+
+impl DerefMut for Padded {
+    fn deref_mut(&mut self) -> &mut AtomicUsize {
+        &mut self.0
+    }
+}
+
+// SAFETY: the slots between head and tail are only touched by the consumer and the rest only by the producer
+unsafe impl<T: Send> Sync for Shared<T> {}
+
+impl<T> Shared<T> {
+    fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+
+    fn len(&self) -> usize {
+        self.tail.load(Ordering::Acquire).wrapping_sub(self.head.load(Ordering::Acquire))
+    }
+
+    fn slot(&self, index: usize) -> *mut T {
+        // the pointer comes from the whole buffer, so it can be used to copy several slots at once
+        // SAFETY: index & mask is always inside the buffer
+        UnsafeCell::raw_get(unsafe { self.buffer.as_ptr().add(index & self.mask) }) as *mut T
+    }
+
+    fn segments(&self, index: usize, n: usize) -> (usize, usize) {
+        // n slots starting at index may wrap around the end of the buffer
+        let first: usize = usize::min(n, self.capacity() - (index & self.mask));
+        (first, n - first)
+    }
+}
+
+impl<T> Drop for Shared<T> {
+    fn drop(&mut self) {
+        let head: usize = *self.head.get_mut();
+        let tail: usize = *self.tail.get_mut();
+        for i in 0..tail.wrapping_sub(head) {
+            // SAFETY: both halves are gone, and the slots between head and tail are initialized
+            unsafe {
+                ptr::drop_in_place(self.slot(head.wrapping_add(i)));
+            }
+        }
+    }
+}
+
+pub struct Producer<T> {
+    shared: Arc<Shared<T>>,
+}
+
+pub struct Consumer<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> RingBuffer<T> {
+    pub fn split(mut self) -> (Producer<T>, Consumer<T>) {
+        // the capacity is rounded up to a power of two, and the elements already in the buffer are kept
+        let capacity: usize = usize::max(self.capacity(), 1).next_power_of_two();
+        let shared: Shared<T> = Shared {
+            buffer: (0..capacity).map(|_| UnsafeCell::new(MaybeUninit::uninit())).collect(),
+            mask: capacity - 1,
+            head: Padded(AtomicUsize::new(0)),
+            tail: Padded(AtomicUsize::new(0)),
+        };
+        let mut tail: usize = 0;
+        while let Some(value) = self.pop_front() {
+            // SAFETY: nobody else has access to the buffer yet
+            unsafe {
+                shared.slot(tail).write(value);
+            }
+            tail += 1;
+        }
+        shared.tail.store(tail, Ordering::Relaxed);
+
+        let shared: Arc<Shared<T>> = Arc::new(shared);
+        (Producer { shared: shared.clone() }, Consumer { shared })
+    }
+}
+
+impl<T> Producer<T> {
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity()
+    }
+
+    pub fn len(&self) -> usize {
+        // the consumer may pop at any moment, so this is an upper bound
+        self.shared.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len() == self.capacity()
+    }
+
+    pub fn push(&mut self, value: T) -> Result<(), T> {
+        // the value is handed back if the buffer is full
+        let tail: usize = self.shared.tail.load(Ordering::Relaxed);
+        let head: usize = self.shared.head.load(Ordering::Acquire);
+        if tail.wrapping_sub(head) == self.capacity() {
+            return Err(value);
+        }
+        // SAFETY: the slot is outside of head..tail, so the consumer won't read it until tail moves past it
+        unsafe {
+            self.shared.slot(tail).write(value);
+        }
+        self.shared.tail.store(tail.wrapping_add(1), Ordering::Release);
+        Ok(())
+    }
+}
+
+impl<T: Copy> Producer<T> {
+    pub fn push_slice(&mut self, values: &[T]) -> usize {
+        // pushes as many values as fit, and returns how many were pushed
+        let tail: usize = self.shared.tail.load(Ordering::Relaxed);
+        let head: usize = self.shared.head.load(Ordering::Acquire);
+        let n: usize = usize::min(values.len(), self.capacity() - tail.wrapping_sub(head));
+        let (first, second) = self.shared.segments(tail, n);
+        // SAFETY: the same as in push, but for n slots at once
+        unsafe {
+            ptr::copy_nonoverlapping(values.as_ptr(), self.shared.slot(tail), first);
+            ptr::copy_nonoverlapping(values.as_ptr().add(first), self.shared.slot(0), second);
+        }
+        self.shared.tail.store(tail.wrapping_add(n), Ordering::Release);
+        n
+    }
+}
+
+impl<T> Consumer<T> {
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity()
+    }
+
+    pub fn len(&self) -> usize {
+        // the producer may push at any moment, so this is a lower bound
+        self.shared.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        let head: usize = self.shared.head.load(Ordering::Relaxed);
+        let tail: usize = self.shared.tail.load(Ordering::Acquire);
+        if head == tail {
+            return None;
+        }
+        // SAFETY: the slot is inside head..tail, so the producer already wrote it and won't touch it
+        // until head moves past it
+        let value: T = unsafe { self.shared.slot(head).read() };
+        self.shared.head.store(head.wrapping_add(1), Ordering::Release);
+        Some(value)
+    }
+
+    pub fn peek(&mut self) -> Option<&T> {
+        // takes &mut self like pop: Consumer is Sync even if T isn't, so shared references to the consumer in
+        // several threads must not give access to the values
+        let head: usize = self.shared.head.load(Ordering::Relaxed);
+        let tail: usize = self.shared.tail.load(Ordering::Acquire);
+        if head == tail {
+            return None;
+        }
+        // SAFETY: the same as in pop, and head can't move while the reference borrows the consumer mutably
+        Some(unsafe { &*self.shared.slot(head) })
+    }
+}
+
+impl<T: Copy> Consumer<T> {
+    pub fn pop_slice(&mut self, values: &mut [T]) -> usize {
+        // fills the slice with as many values as there are, and returns how many were popped
+        let head: usize = self.shared.head.load(Ordering::Relaxed);
+        let tail: usize = self.shared.tail.load(Ordering::Acquire);
+        let n: usize = usize::min(values.len(), tail.wrapping_sub(head));
+        let (first, second) = self.shared.segments(head, n);
+        // SAFETY: the same as in pop, but for n slots at once
+        unsafe {
+            ptr::copy_nonoverlapping(self.shared.slot(head), values.as_mut_ptr(), first);
+            ptr::copy_nonoverlapping(self.shared.slot(0), values.as_mut_ptr().add(first), second);
+        }
+        self.shared.head.store(head.wrapping_add(n), Ordering::Release);
+        n
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ cell::RefCell, rc::Rc, thread };
+
+    use super::*;
+
+    // Miri is way slower, so the stress tests are shorter there
+    const STRESS_LENGTH: usize = if cfg!(miri) { 500 } else { 200_000 };
+
+    #[test]
+    fn push_and_pop() {
+        let mut rf: RingBuffer<i32> = RingBuffer::new(3);
+        rf.push_back(0).unwrap();
+        rf.push_back(1).unwrap();
+
+        let (mut producer, mut consumer) = rf.split();
+        assert_eq!(4, producer.capacity());
+        assert_eq!(2, consumer.len());
+
+        producer.push(2).unwrap();
+        producer.push(3).unwrap();
+        assert!(producer.is_full());
+        assert_eq!(Err(4), producer.push(4));
+
+        assert_eq!(Some(&0), consumer.peek());
+        assert_eq!(Some(0), consumer.pop());
+        assert_eq!(Some(1), consumer.pop());
+        producer.push(4).unwrap();
+        assert_eq!(Some(2), consumer.pop());
+        assert_eq!(Some(3), consumer.pop());
+        assert_eq!(Some(4), consumer.pop());
+        assert_eq!(None, consumer.pop());
+        assert!(producer.is_empty());
+    }
+
+    #[test]
+    fn slices() {
+        let (mut producer, mut consumer) = RingBuffer::<i32>::new(8).split();
+        let mut values: [i32; 6] = [0; 6];
+
+        assert_eq!(5, producer.push_slice(&[0, 1, 2, 3, 4]));
+        assert_eq!(3, consumer.pop_slice(&mut values[..3]));
+        assert_eq!([0, 1, 2], values[..3]);
+
+        // wraps around the end of the buffer
+        assert_eq!(6, producer.push_slice(&[5, 6, 7, 8, 9, 10, 11]));
+        assert_eq!(6, consumer.pop_slice(&mut values));
+        assert_eq!([3, 4, 5, 6, 7, 8], values);
+        assert_eq!(2, consumer.pop_slice(&mut values));
+        assert_eq!([9, 10], values[..2]);
+        assert_eq!(0, consumer.pop_slice(&mut values));
+    }
+
+    #[derive(Debug)]
+    struct DropRecorder {
+        name: &'static str,
+        drops: Rc<RefCell<Vec<&'static str>>>,
+    }
+
+    impl Drop for DropRecorder {
+        fn drop(&mut self) {
+            self.drops.borrow_mut().push(self.name);
+        }
+    }
+
+    #[test]
+    fn drops_remaining_values() {
+        let drops: Rc<RefCell<Vec<&'static str>>> = Rc::new(RefCell::new(vec![]));
+        let value = |name: &'static str| DropRecorder { name, drops: drops.clone() };
+        let (mut producer, mut consumer) = RingBuffer::<DropRecorder>::new(2).split();
+        producer.push(value("a")).unwrap();
+        producer.push(value("b")).unwrap();
+        let popped: DropRecorder = consumer.pop().unwrap();
+        assert_eq!("a", popped.name);
+        producer.push(value("c")).unwrap();
+        let rejected: DropRecorder = producer.push(value("d")).unwrap_err();
+        assert_eq!("d", rejected.name);
+
+        // the values still in the buffer are dropped once both halves are gone, and only then
+        drop(consumer);
+        assert!(drops.borrow().is_empty());
+        drop(producer);
+        assert_eq!(vec!["b", "c"], *drops.borrow());
+
+        drop(popped);
+        drop(rejected);
+        assert_eq!(vec!["b", "c", "a", "d"], *drops.borrow());
+    }
+
+    #[test]
+    fn stress_one_by_one() {
+        let (mut producer, mut consumer) = RingBuffer::<usize>::new(16).split();
+
+        let handle = thread::spawn(move || {
+            for i in 0..STRESS_LENGTH {
+                let mut value: usize = i;
+                while let Err(v) = producer.push(value) {
+                    value = v;
+                    thread::yield_now();
+                }
+            }
+        });
+
+        let mut expected: usize = 0;
+        while expected < STRESS_LENGTH {
+            match consumer.pop() {
+                Some(value) => {
+                    assert_eq!(expected, value);
+                    expected += 1;
+                }
+                None => thread::yield_now(),
+            }
+        }
+        handle.join().unwrap();
+        assert!(consumer.is_empty());
+    }
+
+    #[test]
+    fn stress_slices() {
+        let (mut producer, mut consumer) = RingBuffer::<usize>::new(64).split();
+
+        let handle = thread::spawn(move || {
+            let values: Vec<usize> = (0..STRESS_LENGTH).collect();
+            let mut pushed: usize = 0;
+            while pushed < STRESS_LENGTH {
+                let end: usize = usize::min(pushed + 7 + (pushed % 50), STRESS_LENGTH);
+                pushed += producer.push_slice(&values[pushed..end]);
+                thread::yield_now();
+            }
+        });
+
+        let mut values: [usize; 23] = [0; 23];
+        let mut expected: usize = 0;
+        while expected < STRESS_LENGTH {
+            let n: usize = consumer.pop_slice(&mut values);
+            for value in &values[..n] {
+                assert_eq!(expected, *value);
+                expected += 1;
+            }
+            if n == 0 {
+                thread::yield_now();
+            }
+        }
+        handle.join().unwrap();
+    }
+}
@@ -0,0 +1,207 @@
+#![allow(dead_code)]
+
+use crate::heap::{ MaxHeap, MinHeap };
+
+// Helpers that answer questions about a stream of values with heaps, without keeping the whole stream sorted.
+// TopK keeps the k largest values in a MinHeap: its top is the smallest of them, which is the one that gets replaced
+// when a bigger value comes.
+// RunningMedian splits the values in two halves: the lower one in a MaxHeap and the upper one in a MinHeap, so the
+// middle values are both tops. The lower half has the same amount of values as the upper one, or one more.
+
+pub struct TopK<T: PartialOrd> {
+    k: usize,
+    heap: MinHeap<T>,
+}
+
+impl<T: PartialOrd> TopK<T> {
+    pub fn new(k: usize) -> TopK<T> {
+        TopK { k, heap: MinHeap::new() }
+    }
+
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    pub fn push(&mut self, value: T) {
+        if self.heap.len() < self.k {
+            self.heap.insert(value);
+            return;
+        }
+        if let Some(mut smallest) = self.heap.peek_mut() {
+            if value > *smallest {
+                *smallest = value;
+            }
+        }
+    }
+
+    pub fn smallest(&self) -> Option<&T> {
+        // the smallest of the k largest values, which a new value has to beat to get in
+        self.heap.peek()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        // in no particular order
+        self.heap.iter()
+    }
+
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        // from the largest to the smallest
+        self.heap.into_sorted_vec()
+    }
+}
+
+pub struct RunningMedian<T: PartialOrd> {
+    lower: MaxHeap<T>,
+    upper: MinHeap<T>,
+}
+
+impl<T: PartialOrd> RunningMedian<T> {
+    pub fn new() -> RunningMedian<T> {
+        RunningMedian {
+            lower: MaxHeap::new(),
+            upper: MinHeap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.lower.len() + self.upper.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lower.is_empty()
+    }
+
+    pub fn push(&mut self, value: T) {
+        match self.lower.peek() {
+            Some(top) if value > *top => self.upper.insert(value),
+            _ => self.lower.insert(value),
+        }
+
+        // rebalance, moving the top of the bigger half to the other one
+        if self.lower.len() > self.upper.len() + 1 {
+            let top: T = self.lower.pop().unwrap();
+            self.upper.insert(top);
+        } else if self.upper.len() > self.lower.len() {
+            let top: T = self.upper.pop().unwrap();
+            self.lower.insert(top);
+        }
+    }
+
+    pub fn low_median(&self) -> Option<&T> {
+        // the middle value, or the lower of the two middle values when the amount is even
+        self.lower.peek()
+    }
+
+    pub fn high_median(&self) -> Option<&T> {
+        // the middle value, or the upper of the two middle values when the amount is even
+        if self.lower.len() > self.upper.len() {
+            self.lower.peek()
+        } else {
+            self.upper.peek()
+        }
+    }
+}
+
+impl<T: PartialOrd + Copy + Into<f64>> RunningMedian<T> {
+    pub fn median(&self) -> Option<f64> {
+        // the average of the two middle values when the amount is even
+        let low: f64 = (*self.low_median()?).into();
+        let high: f64 = (*self.high_median()?).into();
+        Some((low + high) / 2.0)
+    }
+}
+
+impl<T: PartialOrd> Default for RunningMedian<T> {
+    fn default() -> RunningMedian<T> {
+        RunningMedian::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::next_random;
+
+    #[test]
+    fn top_k() {
+        let mut top: TopK<i32> = TopK::new(3);
+        assert_eq!(None, top.smallest());
+        for value in [5, 1, 9, 3, 7, 9, 2] {
+            top.push(value);
+        }
+        assert_eq!(3, top.len());
+        assert_eq!(Some(&7), top.smallest());
+        assert_eq!(vec![9, 9, 7], top.into_sorted_vec());
+
+        let mut none: TopK<i32> = TopK::new(0);
+        none.push(1);
+        assert!(none.is_empty());
+    }
+
+    #[test]
+    fn top_k_against_sorted_vec() {
+        let mut seed: u64 = 17;
+        for k in [1, 2, 5, 20] {
+            let mut top: TopK<u64> = TopK::new(k);
+            let mut values: Vec<u64> = vec![];
+            for _ in 0..200 {
+                let value: u64 = next_random(&mut seed) % 50;
+                top.push(value);
+                values.push(value);
+
+                let mut sorted: Vec<u64> = values.clone();
+                sorted.sort_by(|a, b| b.cmp(a));
+                sorted.truncate(k);
+                assert_eq!(sorted.last(), top.smallest());
+            }
+            let mut sorted: Vec<u64> = values.clone();
+            sorted.sort_by(|a, b| b.cmp(a));
+            sorted.truncate(k);
+            assert_eq!(sorted, top.into_sorted_vec());
+        }
+    }
+
+    #[test]
+    fn running_median() {
+        let mut median: RunningMedian<i32> = RunningMedian::new();
+        assert_eq!(None, median.median());
+
+        median.push(5);
+        assert_eq!(Some(5.0), median.median());
+        median.push(1);
+        assert_eq!(Some(&1), median.low_median());
+        assert_eq!(Some(&5), median.high_median());
+        assert_eq!(Some(3.0), median.median());
+        median.push(10);
+        assert_eq!(Some(5.0), median.median());
+        median.push(8);
+        assert_eq!(Some(6.5), median.median());
+        assert_eq!(4, median.len());
+    }
+
+    #[test]
+    fn running_median_against_sorted_vec() {
+        let mut seed: u64 = 23;
+        let mut median: RunningMedian<u32> = RunningMedian::new();
+        let mut sorted: Vec<u32> = vec![];
+        for _ in 0..500 {
+            let value: u32 = (next_random(&mut seed) % 100) as u32;
+            median.push(value);
+            let i: usize = sorted.partition_point(|e| *e < value);
+            sorted.insert(i, value);
+
+            let n: usize = sorted.len();
+            assert_eq!(Some(&sorted[(n - 1) / 2]), median.low_median());
+            assert_eq!(Some(&sorted[n / 2]), median.high_median());
+            assert_eq!(Some((sorted[(n - 1) / 2] as f64 + sorted[n / 2] as f64) / 2.0), median.median());
+        }
+    }
+}
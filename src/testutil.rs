@@ -0,0 +1,7 @@
+// Helpers shared by the tests of several modules
+
+pub fn next_random(seed: &mut u64) -> u64 {
+    // a simple linear congruential generator, so the tests are deterministic
+    *seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+    *seed >> 33
+}
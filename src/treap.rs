@@ -0,0 +1,282 @@
+#![allow(dead_code)]
+
+use crate::orderedset::{ self, OrderedSet, SearchNode };
+
+// A treap is a binary search tree by value and, at the same time, a max-heap by a random priority given to every
+// node. The shape of the tree is the one it would have if the values had been inserted in order of priority, which
+// is a random order, so the expected height is O(log n) whatever the order of the insertions.
+// Everything is built on split (cut the tree in the values before and after some value) and merge (join two trees
+// where all the values of the first one come before the ones of the second one). To insert, the tree is split where
+// the value goes and merged back with the new node in the middle; to remove, the node is split out.
+// The priorities come from a xorshift generator, so a treap built with the same seed always has the same shape.
+
+struct Node<T> {
+    value: T,
+    priority: u64,
+    left: Option<Box<Node<T>>>,
+    right: Option<Box<Node<T>>>,
+    size: usize, // amount of nodes in the subtree
+}
+
+type Link<T> = Option<Box<Node<T>>>;
+
+fn size<T>(node: &Option<Box<Node<T>>>) -> usize {
+    node.as_ref().map_or(0, |n| n.size)
+}
+
+impl<T> Node<T> {
+    fn update_size(&mut self) {
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+
+    fn split<F: Fn(&T) -> bool>(
+        node: Link<T>,
+        goes_left: &F
+    ) -> (Link<T>, Link<T>) {
+        // goes_left must be true for the values before some point and false after it
+        match node {
+            None => (None, None),
+            Some(mut node) => {
+                if goes_left(&node.value) {
+                    let (left, right) = Self::split(node.right.take(), goes_left);
+                    node.right = left;
+                    node.update_size();
+                    (Some(node), right)
+                } else {
+                    let (left, right) = Self::split(node.left.take(), goes_left);
+                    node.left = right;
+                    node.update_size();
+                    (left, Some(node))
+                }
+            }
+        }
+    }
+
+    fn merge(left: Link<T>, right: Link<T>) -> Link<T> {
+        // all the values of left must come before the ones of right. The root with the highest priority stays on top
+        match (left, right) {
+            (None, right) => right,
+            (left, None) => left,
+            (Some(mut left), Some(mut right)) => {
+                if left.priority > right.priority {
+                    left.right = Self::merge(left.right.take(), Some(right));
+                    left.update_size();
+                    Some(left)
+                } else {
+                    right.left = Self::merge(Some(left), right.left.take());
+                    right.update_size();
+                    Some(right)
+                }
+            }
+        }
+    }
+}
+
+impl<T> SearchNode<T> for Node<T> {
+    fn value(&self) -> &T {
+        &self.value
+    }
+
+    fn left(&self) -> Option<&Node<T>> {
+        self.left.as_deref()
+    }
+
+    fn right(&self) -> Option<&Node<T>> {
+        self.right.as_deref()
+    }
+}
+
+pub struct Treap<T> {
+    root: Option<Box<Node<T>>>,
+    state: u64, // of the priority generator
+}
+
+impl<T: PartialOrd> Treap<T> {
+    pub fn new() -> Treap<T> {
+        Treap::with_seed(0x2545F4914F6CDD1D)
+    }
+
+    pub fn with_seed(seed: u64) -> Treap<T> {
+        Treap {
+            root: None,
+            state: seed.max(1), // xorshift gets stuck at 0
+        }
+    }
+
+    fn next_priority(&mut self) -> u64 {
+        // xorshift64
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    pub fn height(&self) -> usize {
+        fn height<T>(node: &Option<Box<Node<T>>>) -> usize {
+            node.as_ref().map_or(0, |n| 1 + height(&n.left).max(height(&n.right)))
+        }
+        height(&self.root)
+    }
+
+    pub fn split(mut self, value: &T) -> (Treap<T>, Treap<T>) {
+        // the values smaller than value, and the rest. Their generators are seeded from this one
+        let (left, right) = Node::split(self.root.take(), &(|v: &T| v < value));
+        let left_state: u64 = self.next_priority();
+        let right_state: u64 = self.next_priority();
+        (Treap { root: left, state: left_state }, Treap { root: right, state: right_state })
+    }
+
+    pub fn merge(mut self, other: Treap<T>) -> Treap<T> {
+        // all the values of self must be smaller than the ones of other
+        if let (Some(max), Some(min)) = (orderedset::max(self.root.as_deref()), orderedset::min(other.root.as_deref())) {
+            assert!(max < min, "the values of the first treap must be smaller than the ones of the second one");
+        }
+        self.root = Node::merge(self.root.take(), other.root);
+        self
+    }
+}
+
+impl<T: PartialOrd> OrderedSet<T> for Treap<T> {
+    fn insert(&mut self, value: T) -> bool {
+        if self.contains(&value) {
+            return false;
+        }
+        let (left, right) = Node::split(self.root.take(), &(|v: &T| *v < value));
+        let node: Box<Node<T>> = Box::new(Node {
+            value,
+            priority: self.next_priority(),
+            left: None,
+            right: None,
+            size: 1,
+        });
+        self.root = Node::merge(Node::merge(left, Some(node)), right);
+        true
+    }
+
+    fn remove(&mut self, value: &T) -> bool {
+        let (left, rest) = Node::split(self.root.take(), &(|v: &T| v < value));
+        let (found, right) = Node::split(rest, &(|v: &T| v <= value));
+        self.root = Node::merge(left, right);
+        found.is_some()
+    }
+
+    fn contains(&mut self, value: &T) -> bool {
+        self.floor(value) == Some(value)
+    }
+
+    fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    fn min(&mut self) -> Option<&T> {
+        orderedset::min(self.root.as_deref())
+    }
+
+    fn max(&mut self) -> Option<&T> {
+        orderedset::max(self.root.as_deref())
+    }
+
+    fn floor(&mut self, value: &T) -> Option<&T> {
+        orderedset::last_where(self.root.as_deref(), |v| v <= value)
+    }
+
+    fn ceiling(&mut self, value: &T) -> Option<&T> {
+        orderedset::first_where(self.root.as_deref(), |v| v >= value)
+    }
+
+    fn predecessor(&mut self, value: &T) -> Option<&T> {
+        orderedset::last_where(self.root.as_deref(), |v| v < value)
+    }
+
+    fn successor(&mut self, value: &T) -> Option<&T> {
+        orderedset::first_where(self.root.as_deref(), |v| v > value)
+    }
+
+    fn in_order_traverse<F: FnMut(&T)>(&self, visit_func: &mut F) {
+        orderedset::in_order_traverse(self.root.as_deref(), visit_func);
+    }
+}
+
+impl<T: PartialOrd> Default for Treap<T> {
+    fn default() -> Treap<T> {
+        Treap::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(treap: &Treap<i32>) -> Vec<i32> {
+        let mut values: Vec<i32> = vec![];
+        treap.in_order_traverse(&mut (|value: &i32| values.push(*value)));
+        values
+    }
+
+    fn check_heap(node: &Option<Box<Node<i32>>>) {
+        // every node has a higher priority than its children
+        if let Some(n) = node.as_ref() {
+            for child in [&n.left, &n.right] {
+                if let Some(c) = child.as_ref() {
+                    assert!(c.priority < n.priority);
+                }
+                check_heap(child);
+            }
+        }
+    }
+
+    #[test]
+    fn treap_split_and_merge() {
+        let mut treap: Treap<i32> = Treap::with_seed(7);
+        for value in 0..10 {
+            treap.insert(value * 10);
+        }
+        check_heap(&treap.root);
+
+        let (left, right) = treap.split(&45);
+        assert_eq!(vec![0, 10, 20, 30, 40], values(&left));
+        assert_eq!(vec![50, 60, 70, 80, 90], values(&right));
+        assert_eq!(5, right.len());
+
+        let mut treap: Treap<i32> = left.merge(right);
+        check_heap(&treap.root);
+        assert_eq!(10, treap.len());
+        assert!(treap.remove(&40));
+        assert!(!treap.remove(&40));
+        assert_eq!(vec![0, 10, 20, 30, 50, 60, 70, 80, 90], values(&treap));
+    }
+
+    #[test]
+    #[should_panic]
+    fn treap_merge_out_of_order() {
+        let mut a: Treap<i32> = Treap::new();
+        let mut b: Treap<i32> = Treap::new();
+        a.insert(5);
+        b.insert(3);
+        a.merge(b);
+    }
+
+    #[test]
+    fn treap_seeded_shape() {
+        // the same seed gives the same tree, and sorted insertions still give a shallow one
+        let build = |seed: u64| {
+            let mut treap: Treap<i32> = Treap::with_seed(seed);
+            for value in 0..10_000 {
+                treap.insert(value);
+            }
+            treap
+        };
+        fn same_shape(a: &Option<Box<Node<i32>>>, b: &Option<Box<Node<i32>>>) -> bool {
+            match (a.as_ref(), b.as_ref()) {
+                (None, None) => true,
+                (Some(a), Some(b)) => a.value == b.value && same_shape(&a.left, &b.left) && same_shape(&a.right, &b.right),
+                _ => false,
+            }
+        }
+        let a: Treap<i32> = build(1);
+        assert!(same_shape(&a.root, &build(1).root));
+        assert!(!same_shape(&a.root, &build(2).root));
+        assert!(a.height() < 50);
+        check_heap(&a.root);
+    }
+}
@@ -1,6 +1,8 @@
 #![allow(dead_code)]
 
-use std::{ collections::VecDeque, ops::{ Deref, DerefMut } };
+use std::{ collections::VecDeque, fmt::Display, ops::{ Deref, DerefMut } };
+
+use crate::dot;
 
 struct Tree<T> {
     value: T,
@@ -67,6 +69,13 @@ impl<T: PartialEq> Tree<T> {
     }
 }
 
+impl<T: PartialEq + Display> Tree<T> {
+    pub fn to_dot(&self, path: &[T]) -> String {
+        // Graphviz DOT text. path is a walk down from the root, whose nodes are highlighted (see dot::tree)
+        dot::tree(Some(self), |node| &node.value, |node| node.children.iter().map(|c| (None, c.deref())), path)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -142,4 +151,33 @@ mod tests {
         assert!(!Tree::compare(&tree1, &tree3));
         assert!(!Tree::compare(&tree3, &tree2));
     }
+
+    #[test]
+    fn tree_to_dot() {
+        let mut tree: Tree<&str> = Tree::new("root");
+        tree.add_child("a");
+        tree.add_child("b");
+        tree.add_child("c");
+        tree.get_child(1).add_child("d");
+        tree.get_child(1).add_child("e");
+
+        let expected: &str = "\
+digraph {
+    n0 [label=\"root\", color=red];
+    n0 -> n1;
+    n1 [label=\"a\"];
+    n0 -> n2 [color=red, penwidth=2];
+    n2 [label=\"b\", color=red];
+    n2 -> n3;
+    n3 [label=\"d\"];
+    n2 -> n4 [color=red, penwidth=2];
+    n4 [label=\"e\", color=red];
+    n0 -> n5;
+    n5 [label=\"c\"];
+}
+";
+        assert_eq!(expected, tree.to_dot(&["root", "b", "e"]));
+        // a path that doesn't start at the root highlights nothing
+        assert!(!tree.to_dot(&["b", "e"]).contains("red"));
+    }
 }